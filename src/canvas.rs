@@ -0,0 +1,174 @@
+use crate::geometry::IntRect;
+use crate::rules::{Rule, WindowInfo};
+
+/// Which descendant of a matched window is tracked instead of its frame,
+/// for apps that draw the canvas in a child window.
+///
+/// Parsed from `largest`, `id=N` (a control ID) or a rule matched against
+/// the child's class and title, e.g. `class=QOpenGLWidget`.
+#[derive(Debug, Clone)]
+pub enum CanvasSelector {
+    /// The largest visible descendant.
+    Largest,
+    /// The largest visible descendant with this control ID.
+    Id(i32),
+    /// The largest visible descendant matching this rule.
+    Rule(Rule),
+}
+
+/// A visible descendant of the tracked window.
+#[derive(Debug, Clone)]
+pub struct Child {
+    /// Title and class of the child; process and path are its window's.
+    pub info: WindowInfo,
+    /// Control ID, 0 if it has none.
+    pub id: i32,
+    /// Bounds in screen pixels.
+    pub rect: IntRect,
+}
+
+impl CanvasSelector {
+    fn accepts(&self, child: &Child) -> bool {
+        match self {
+            CanvasSelector::Largest => true,
+            CanvasSelector::Id(id) => child.id == *id,
+            CanvasSelector::Rule(rule) => rule.matches(&child.info),
+        }
+    }
+
+    /// Index of the child to track: the largest one the selector accepts,
+    /// the first in `children` on ties. Returns `None` if none is accepted.
+    ///
+    /// Picking the largest skips thumbnails and previews that share the
+    /// canvas class.
+    pub fn select(&self, children: &[Child]) -> Option<usize> {
+        children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| !child.rect.is_empty() && self.accepts(child))
+            .rev()
+            .max_by_key(|(_, child)| child.rect.area())
+            .map(|(i, _)| i)
+    }
+}
+
+impl std::str::FromStr for CanvasSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("largest") {
+            return Ok(CanvasSelector::Largest);
+        }
+        if let Some((key, value)) = s.split_once('=')
+            && key.trim().eq_ignore_ascii_case("id")
+        {
+            let value = value.trim();
+            return value
+                .parse()
+                .map(CanvasSelector::Id)
+                .map_err(|_| format!("'{value}' is not a valid control ID"));
+        }
+        // A plain pattern would match the title or process name, and every
+        // child shares its window's process
+        if !s.contains('=') {
+            return Err(format!(
+                "'{s}' is not a canvas (expected largest, id=N, or a rule like class=NAME)"
+            ));
+        }
+        s.parse().map(CanvasSelector::Rule)
+    }
+}
+
+impl std::fmt::Display for CanvasSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CanvasSelector::Largest => f.write_str("largest"),
+            CanvasSelector::Id(id) => write!(f, "id={id}"),
+            CanvasSelector::Rule(rule) => rule.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn child(class: &str, id: i32, rect: (i32, i32, i32, i32)) -> Child {
+        let (left, top, right, bottom) = rect;
+        Child {
+            info: WindowInfo {
+                class: class.to_string(),
+                process: "krita.exe".to_string(),
+                ..Default::default()
+            },
+            id,
+            rect: IntRect {
+                left,
+                top,
+                right,
+                bottom,
+            },
+        }
+    }
+
+    fn krita() -> Vec<Child> {
+        vec![
+            child("QToolBar", 0, (0, 0, 1600, 40)),
+            child("QOpenGLWidget", 0, (1300, 40, 1600, 240)),
+            child("QOpenGLWidget", 0, (0, 40, 1300, 900)),
+            child("QDockWidget", 1001, (1300, 240, 1600, 900)),
+        ]
+    }
+
+    #[test]
+    fn largest_picks_the_biggest_child() {
+        let selector: CanvasSelector = "largest".parse().unwrap();
+        assert_eq!(selector.select(&krita()), Some(2));
+    }
+
+    #[test]
+    fn rule_picks_the_largest_match() {
+        // The small overview shares the canvas class
+        let selector: CanvasSelector = "class:exact=qopenglwidget".parse().unwrap();
+        assert_eq!(selector.select(&krita()), Some(2));
+
+        let selector: CanvasSelector = "class=Dock".parse().unwrap();
+        assert_eq!(selector.select(&krita()), Some(3));
+    }
+
+    #[test]
+    fn id_picks_the_control() {
+        let selector: CanvasSelector = "id=1001".parse().unwrap();
+        assert_eq!(selector.select(&krita()), Some(3));
+        let selector: CanvasSelector = "ID = 7".parse().unwrap();
+        assert_eq!(selector.select(&krita()), None);
+    }
+
+    #[test]
+    fn ties_and_empty_children() {
+        let children = vec![
+            child("Canvas", 0, (0, 0, 0, 0)),
+            child("Canvas", 0, (0, 0, 800, 600)),
+            child("Canvas", 0, (800, 0, 1600, 600)),
+        ];
+        let selector: CanvasSelector = "class=canvas".parse().unwrap();
+        assert_eq!(selector.select(&children), Some(1));
+        assert_eq!(selector.select(&children[..1]), None);
+        assert_eq!(CanvasSelector::Largest.select(&[]), None);
+    }
+
+    #[test]
+    fn invalid_selectors_are_rejected() {
+        for s in ["", "canvas", "id=", "id=abc", "class:fuzzy=x"] {
+            assert!(s.parse::<CanvasSelector>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for s in ["largest", "id=59648", "class:glob=Qt*GLWidget"] {
+            assert_eq!(s.parse::<CanvasSelector>().unwrap().to_string(), s);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use crate::geometry::{Point, Px};
+use windows::Win32::Foundation::POINT;
+use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+/// Get the cursor position in screen coordinates.
+pub fn position() -> Option<Point<Px>> {
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point) }.ok()?;
+    Some(Point::new(point.x as f64, point.y as f64))
+}
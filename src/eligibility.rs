@@ -0,0 +1,237 @@
+use crate::geometry::{Px, Size};
+
+/// Window class of standard dialog boxes (file pickers, message boxes, ...).
+pub const DIALOG_CLASS: &str = "#32770";
+
+/// What decides whether a window can be tracked, as read from the window
+/// system.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowTraits {
+    /// Owned by another window, as dialogs, palettes and popups are.
+    pub owned: bool,
+    /// Has the `WS_EX_TOOLWINDOW` style (floating palettes, tooltips).
+    pub tool_window: bool,
+    /// Is a standard dialog box (window class [`DIALOG_CLASS`]).
+    pub dialog: bool,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Why a window can't be tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    Owned,
+    ToolWindow,
+    Dialog,
+    TooSmall,
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Rejection::Owned => "owned by another window",
+            Rejection::ToolWindow => "tool window",
+            Rejection::Dialog => "dialog",
+            Rejection::TooSmall => "below the minimum window size",
+        })
+    }
+}
+
+/// Which window to track when a window matches a target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// The window itself.
+    Window,
+    /// The window's owner: the window is a popup of a trackable window.
+    Owner,
+    /// Neither, for the given reason.
+    Neither(Rejection),
+}
+
+/// Which windows can be tracked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Eligibility {
+    /// Track owned windows, tool windows and dialogs as well.
+    pub popups: bool,
+    /// Smallest window that can be tracked.
+    pub min_size: Size<Px>,
+}
+
+impl Eligibility {
+    /// Check whether `window` can be tracked itself.
+    pub fn check(&self, window: &WindowTraits) -> Result<(), Rejection> {
+        if !self.popups {
+            if window.tool_window {
+                return Err(Rejection::ToolWindow);
+            }
+            if window.dialog {
+                return Err(Rejection::Dialog);
+            }
+            if window.owned {
+                return Err(Rejection::Owned);
+            }
+        }
+        if (window.width as f64) < self.min_size.width
+            || (window.height as f64) < self.min_size.height
+        {
+            return Err(Rejection::TooSmall);
+        }
+        Ok(())
+    }
+
+    /// Decide what to track for `window`, given its root `owner` if it has
+    /// one. Popups hand tracking over to the owner they belong to, so a
+    /// color picker or file dialog keeps the mapping on the main window.
+    pub fn decide(&self, window: &WindowTraits, owner: Option<&WindowTraits>) -> Decision {
+        let rejection = match self.check(window) {
+            Ok(()) => return Decision::Window,
+            Err(rejection) => rejection,
+        };
+        match owner {
+            Some(owner) if window.owned && self.check(owner).is_ok() => Decision::Owner,
+            _ => Decision::Neither(rejection),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: Eligibility = Eligibility {
+        popups: false,
+        min_size: Size::new(200.0, 150.0),
+    };
+
+    fn main_window() -> WindowTraits {
+        WindowTraits {
+            width: 1600,
+            height: 900,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn main_window_is_tracked() {
+        assert_eq!(POLICY.check(&main_window()), Ok(()));
+        assert_eq!(POLICY.decide(&main_window(), None), Decision::Window);
+    }
+
+    #[test]
+    fn popups_are_rejected() {
+        let palette = WindowTraits {
+            owned: true,
+            tool_window: true,
+            ..main_window()
+        };
+        let dialog = WindowTraits {
+            owned: true,
+            dialog: true,
+            ..main_window()
+        };
+        let owned = WindowTraits {
+            owned: true,
+            ..main_window()
+        };
+        assert_eq!(POLICY.check(&palette), Err(Rejection::ToolWindow));
+        assert_eq!(POLICY.check(&dialog), Err(Rejection::Dialog));
+        assert_eq!(POLICY.check(&owned), Err(Rejection::Owned));
+    }
+
+    #[test]
+    fn small_windows_are_rejected() {
+        for (width, height) in [(199, 900), (1600, 149), (120, 80)] {
+            let small = WindowTraits {
+                width,
+                height,
+                ..Default::default()
+            };
+            assert_eq!(POLICY.check(&small), Err(Rejection::TooSmall));
+        }
+    }
+
+    #[test]
+    fn popup_hands_tracking_to_its_owner() {
+        let color_picker = WindowTraits {
+            owned: true,
+            dialog: true,
+            width: 400,
+            height: 300,
+            ..Default::default()
+        };
+        assert_eq!(
+            POLICY.decide(&color_picker, Some(&main_window())),
+            Decision::Owner
+        );
+
+        // A tiny owned popup still belongs to its owner
+        let tooltip = WindowTraits {
+            owned: true,
+            width: 80,
+            height: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            POLICY.decide(&tooltip, Some(&main_window())),
+            Decision::Owner
+        );
+    }
+
+    #[test]
+    fn popup_of_untrackable_owner_is_ignored() {
+        let dialog = WindowTraits {
+            owned: true,
+            dialog: true,
+            ..main_window()
+        };
+        let hidden_owner = WindowTraits {
+            tool_window: true,
+            ..main_window()
+        };
+        assert_eq!(
+            POLICY.decide(&dialog, Some(&hidden_owner)),
+            Decision::Neither(Rejection::Dialog)
+        );
+        assert_eq!(
+            POLICY.decide(&dialog, None),
+            Decision::Neither(Rejection::Dialog)
+        );
+    }
+
+    #[test]
+    fn unowned_small_window_is_not_redirected() {
+        let splash = WindowTraits {
+            width: 100,
+            height: 100,
+            ..Default::default()
+        };
+        assert_eq!(
+            POLICY.decide(&splash, Some(&main_window())),
+            Decision::Neither(Rejection::TooSmall)
+        );
+    }
+
+    #[test]
+    fn popups_flag_allows_popups_but_keeps_size_limit() {
+        let policy = Eligibility {
+            popups: true,
+            ..POLICY
+        };
+        let dialog = WindowTraits {
+            owned: true,
+            dialog: true,
+            ..main_window()
+        };
+        assert_eq!(
+            policy.decide(&dialog, Some(&main_window())),
+            Decision::Window
+        );
+        let tiny = WindowTraits {
+            owned: true,
+            width: 50,
+            height: 50,
+            ..Default::default()
+        };
+        assert_eq!(policy.check(&tiny), Err(Rejection::TooSmall));
+    }
+}
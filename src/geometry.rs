@@ -1,120 +1,2398 @@
-/// Display area in OTD format: width, height, center_x, center_y.
-#[derive(Debug, Clone, PartialEq)]
-pub struct DisplayArea {
+use std::marker::PhantomData;
+
+/// Unit tag for screen pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Px;
+
+/// Unit tag for tablet millimetres.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Mm;
+
+/// A point in unit `U`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point<U> {
+    pub x: f64,
+    pub y: f64,
+    unit: PhantomData<U>,
+}
+
+impl<U> Point<U> {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Point {
+            x,
+            y,
+            unit: PhantomData,
+        }
+    }
+}
+
+impl<U> std::str::FromStr for Point<U> {
+    type Err = String;
+
+    /// Parses `X,Y` (`-500,300`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| format!("'{s}' must be X,Y"))?;
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("'{v}' in '{s}' is not a valid coordinate"))
+        };
+        Ok(Point::new(parse(x)?, parse(y)?))
+    }
+}
+
+/// A width and height in unit `U`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size<U> {
     pub width: f64,
     pub height: f64,
-    pub center_x: f64,
-    pub center_y: f64,
+    unit: PhantomData<U>,
+}
+
+impl<U> Size<U> {
+    pub const fn new(width: f64, height: f64) -> Self {
+        Size {
+            width,
+            height,
+            unit: PhantomData,
+        }
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        self.width / self.height
+    }
+}
+
+impl<U> std::str::FromStr for Size<U> {
+    type Err = String;
+
+    /// Parses `WIDTHxHEIGHT` (`1200x750`), both positive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height) = s
+            .split_once(['x', 'X'])
+            .ok_or_else(|| format!("'{s}' must be WIDTHxHEIGHT"))?;
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v > 0.0)
+                .ok_or_else(|| format!("'{v}' in '{s}' is not a positive number"))
+        };
+        Ok(Size::new(parse(width)?, parse(height)?))
+    }
+}
+
+/// A rectangle in center form (the form OTD uses), in unit `U`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect<U> {
+    pub center: Point<U>,
+    pub size: Size<U>,
+}
+
+impl<U> Rect<U> {
+    pub const fn new(center: Point<U>, size: Size<U>) -> Self {
+        Rect { center, size }
+    }
+
+    /// Build a rectangle from its left, top, right and bottom edges.
+    pub fn from_edges(left: f64, top: f64, right: f64, bottom: f64) -> Self {
+        Rect::new(
+            Point::new((left + right) / 2.0, (top + bottom) / 2.0),
+            Size::new(right - left, bottom - top),
+        )
+    }
+
+    pub fn width(&self) -> f64 {
+        self.size.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.size.height
+    }
+
+    pub fn left(&self) -> f64 {
+        self.center.x - self.size.width / 2.0
+    }
+
+    pub fn top(&self) -> f64 {
+        self.center.y - self.size.height / 2.0
+    }
+
+    pub fn right(&self) -> f64 {
+        self.center.x + self.size.width / 2.0
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.center.y + self.size.height / 2.0
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        self.size.aspect_ratio()
+    }
+
+    /// True if `point` lies inside the rectangle or on its edge.
+    pub fn contains(&self, point: Point<U>) -> bool {
+        (self.left()..=self.right()).contains(&point.x)
+            && (self.top()..=self.bottom()).contains(&point.y)
+    }
+
+    /// Check that every coordinate is finite and the size is positive.
+    pub fn validate(self) -> Result<Self, String> {
+        let values = [
+            self.center.x,
+            self.center.y,
+            self.size.width,
+            self.size.height,
+        ];
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err(format!(
+                "area [{}x{}@<{}, {}>] has a non-finite coordinate",
+                self.size.width, self.size.height, self.center.x, self.center.y
+            ));
+        }
+        if self.size.width <= 0.0 || self.size.height <= 0.0 {
+            return Err(format!(
+                "area [{}x{}] has no size",
+                self.size.width, self.size.height
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// A rectangle of whole pixels in edge form, as Win32 reports window and
+/// monitor bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IntRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl IntRect {
+    /// Build a rectangle from its top-left corner and size.
+    pub const fn from_size(left: i32, top: i32, width: i32, height: i32) -> Self {
+        IntRect {
+            left,
+            top,
+            right: left + width,
+            bottom: top + height,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    /// True if the rectangle has zero or negative width or height.
+    pub fn is_empty(&self) -> bool {
+        self.width() <= 0 || self.height() <= 0
+    }
+
+    /// True if `point` lies inside the rectangle. The right and bottom edges
+    /// are exclusive, as in Win32.
+    pub fn contains(&self, point: Point<Px>) -> bool {
+        point.x >= self.left as f64
+            && point.x < self.right as f64
+            && point.y >= self.top as f64
+            && point.y < self.bottom as f64
+    }
+
+    /// Number of pixels covered, or 0 if the rectangle is empty.
+    pub fn area(&self) -> i64 {
+        if self.is_empty() {
+            0
+        } else {
+            self.width() as i64 * self.height() as i64
+        }
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &IntRect) -> IntRect {
+        IntRect {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    /// The overlap of two rectangles, or `None` if they don't overlap.
+    pub fn intersect(&self, other: &IntRect) -> Option<IntRect> {
+        let overlap = IntRect {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        };
+        (!overlap.is_empty()).then_some(overlap)
+    }
+
+    /// The same rectangle in center form.
+    pub fn to_rect(self) -> Rect<Px> {
+        Rect::from_edges(
+            self.left as f64,
+            self.top as f64,
+            self.right as f64,
+            self.bottom as f64,
+        )
+    }
+}
+
+impl std::str::FromStr for IntRect {
+    type Err = String;
+
+    /// Parses `LEFT,TOP,WIDTH,HEIGHT` in pixels (`-1920,0,1920,1080`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(',')
+            .map(|v| {
+                v.trim()
+                    .parse::<i32>()
+                    .map_err(|_| format!("'{v}' is not a whole number of pixels"))
+            })
+            .collect::<Result<Vec<i32>, _>>()?;
+        let [left, top, width, height] = values[..] else {
+            return Err(format!("'{s}' must be four values (left,top,width,height)"));
+        };
+        if width <= 0 || height <= 0 {
+            return Err(format!("rect '{s}' must have a positive size"));
+        }
+        Ok(IntRect::from_size(left, top, width, height))
+    }
+}
+
+/// Display area in OTD format: size and center, in screen pixels.
+pub type DisplayArea = Rect<Px>;
+
+impl DisplayArea {
+    /// How far the area's aspect ratio deviates from the tablet's, as a
+    /// fraction: 0.0 is undistorted, 0.1 means strokes are stretched 10%
+    /// along one axis.
+    pub fn distortion(&self, tablet_aspect_ratio: f64) -> f64 {
+        let ratio = self.aspect_ratio() / tablet_aspect_ratio;
+        ratio.max(1.0 / ratio) - 1.0
+    }
 }
 
-/// Tablet area including rotation.
-#[derive(Debug, Clone, PartialEq)]
+/// Tablet area including rotation, in tablet millimetres.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TabletArea {
-    pub width: f64,
-    pub height: f64,
-    pub center_x: f64,
-    pub center_y: f64,
+    pub rect: Rect<Mm>,
     pub rotation: f64,
 }
 
-/// Compute the smallest display area that fully covers the given window
-/// while preserving the given aspect ratio, centered on the window.
+/// Re-orient `tablet` to `rotation` degrees, keeping its center and its
+/// shape as far as the tablet allows.
 ///
-/// This ensures the pen can reach every part of the window. The mapped area
-/// may extend slightly beyond the window in one direction to maintain the
-/// tablet's aspect ratio.
+/// The result is the largest rectangle that, rotated by `rotation`, still
+/// fits the physical footprint of `tablet` — its bounding box at its current
+/// rotation. The rectangle keeps the footprint's aspect ratio in whichever
+/// orientation (landscape or portrait) yields the larger area, so a quarter
+/// turn swaps width and height while a slight tilt shrinks the area just
+/// enough to keep its corners on the tablet.
 ///
-/// Returns `None` if the window has zero or negative dimensions.
-pub fn fit_to_window(
-    window_left: i32,
-    window_top: i32,
-    window_width: i32,
-    window_height: i32,
-    tablet_aspect_ratio: f64,
-) -> Option<DisplayArea> {
-    if window_width <= 0 || window_height <= 0 {
-        return None;
+/// OTD maps the area's width onto the display's width whatever the rotation,
+/// so the result's aspect ratio is the one the display area must match.
+pub fn rotate_tablet_area(tablet: &TabletArea, rotation: f64) -> TabletArea {
+    if rotation == tablet.rotation {
+        return *tablet;
     }
 
-    let w = window_width as f64;
-    let h = window_height as f64;
-    let window_aspect = w / h;
+    let (sin, cos) = tablet.rotation.to_radians().sin_cos();
+    let (sin, cos) = (sin.abs(), cos.abs());
+    let size = tablet.rect.size;
+    let bounds = Size::new(
+        size.width * cos + size.height * sin,
+        size.width * sin + size.height * cos,
+    );
 
-    let (fit_w, fit_h) = if window_aspect > tablet_aspect_ratio {
-        // Window is wider than tablet ratio — expand height to cover width
-        (w, w / tablet_aspect_ratio)
+    let aspect = bounds.aspect_ratio();
+    let landscape = largest_rotated_rect(bounds, aspect, rotation);
+    let portrait = largest_rotated_rect(bounds, 1.0 / aspect, rotation);
+    let size = if portrait.width * portrait.height > landscape.width * landscape.height {
+        portrait
     } else {
-        // Window is taller than tablet ratio — expand width to cover height
-        (h * tablet_aspect_ratio, h)
+        landscape
+    };
+
+    TabletArea {
+        rect: Rect::new(tablet.rect.center, size),
+        rotation,
+    }
+}
+
+/// Size of the largest rectangle with the given aspect ratio that, rotated
+/// by `rotation` degrees, fits inside `bounds`.
+fn largest_rotated_rect(bounds: Size<Mm>, aspect: f64, rotation: f64) -> Size<Mm> {
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (sin, cos) = (sin.abs(), cos.abs());
+
+    // Bounding box of an `aspect` x 1 rectangle rotated by `rotation`
+    let scale = (bounds.width / (aspect * cos + sin)).min(bounds.height / (aspect * sin + cos));
+    Size::new(aspect * scale, scale)
+}
+
+/// A length on the tablet, from an edge of the tablet area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TabletLength {
+    /// Absolute length in millimetres.
+    Millimetres(f64),
+    /// Percentage of the tablet area's width (left, width) or height (top,
+    /// height).
+    Percent(f64),
+}
+
+impl TabletLength {
+    fn resolve(self, extent: f64) -> f64 {
+        match self {
+            TabletLength::Millimetres(mm) => mm,
+            TabletLength::Percent(pct) => extent * pct / 100.0,
+        }
+    }
+}
+
+/// Parse a non-negative length: a percentage (`10%`) or a number with an
+/// optional unit suffix (`10`, `10mm`). Returns the number and whether it is
+/// a percentage.
+pub fn parse_length(s: &str, unit_suffix: &str) -> Result<(f64, bool), String> {
+    let s = s.trim();
+    let (number, percent) = match s.strip_suffix('%') {
+        Some(number) => (number, true),
+        None => (s.strip_suffix(unit_suffix).unwrap_or(s), false),
     };
+    let v: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{s}' is not a valid length"))?;
+    if !v.is_finite() || v < 0.0 {
+        return Err(format!("length '{s}' must not be negative"));
+    }
+    Ok((v, percent))
+}
+
+impl std::str::FromStr for TabletLength {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (v, percent) = parse_length(s, "mm")?;
+        Ok(if percent {
+            TabletLength::Percent(v)
+        } else {
+            TabletLength::Millimetres(v)
+        })
+    }
+}
+
+/// Part of the tablet area to draw on, measured from the area's top-left
+/// corner in its own (rotated) frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TabletRegion {
+    pub left: TabletLength,
+    pub top: TabletLength,
+    pub width: TabletLength,
+    pub height: TabletLength,
+}
+
+impl std::str::FromStr for TabletRegion {
+    type Err = String;
+
+    /// Parses `LEFT,TOP,WIDTH,HEIGHT`, where each value is millimetres
+    /// (`20`, `20mm`) or a percentage of the tablet area (`10%`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<TabletLength>, _>>()?;
+        match values[..] {
+            [left, top, width, height] => Ok(TabletRegion {
+                left,
+                top,
+                width,
+                height,
+            }),
+            _ => Err(format!("'{s}' must be four values (left,top,width,height)")),
+        }
+    }
+}
+
+/// Restrict `tablet` to `region`, keeping its rotation.
+///
+/// Returns `None` if the region has no area or extends past the tablet area.
+pub fn restrict_tablet_area(tablet: &TabletArea, region: &TabletRegion) -> Option<TabletArea> {
+    let full = tablet.rect.size;
+    let left = region.left.resolve(full.width);
+    let top = region.top.resolve(full.height);
+    let size = Size::new(
+        region.width.resolve(full.width),
+        region.height.resolve(full.height),
+    );
+
+    // Allow for rounding in percentages that add up to 100
+    const SLACK: f64 = 1e-6;
+    if size.width <= 0.0
+        || size.height <= 0.0
+        || left + size.width > full.width + SLACK
+        || top + size.height > full.height + SLACK
+    {
+        return None;
+    }
 
-    let center_x = window_left as f64 + w / 2.0;
-    let center_y = window_top as f64 + h / 2.0;
+    // Offset of the region's center from the area's center, in the area's
+    // frame, then rotated into tablet coordinates
+    let dx = left + size.width / 2.0 - full.width / 2.0;
+    let dy = top + size.height / 2.0 - full.height / 2.0;
+    let (sin, cos) = tablet.rotation.to_radians().sin_cos();
+    let center = tablet.rect.center;
 
-    Some(DisplayArea {
-        width: fit_w,
-        height: fit_h,
-        center_x,
-        center_y,
+    Some(TabletArea {
+        rect: Rect::new(
+            Point::new(
+                center.x + dx * cos - dy * sin,
+                center.y + dx * sin + dy * cos,
+            ),
+            size,
+        ),
+        rotation: tablet.rotation,
     })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Crop `tablet` to the largest area with the given aspect ratio, positioned
+/// inside the original according to `anchor`.
+///
+/// The crop happens in the area's own (rotated) frame, so anchoring to the
+/// left keeps the area's left edge wherever the rotation puts it on the
+/// tablet. Returns the tablet area unchanged if the aspect ratio is invalid.
+pub fn crop_tablet_area(tablet: &TabletArea, aspect_ratio: f64, anchor: Anchor) -> TabletArea {
+    if !aspect_ratio.is_finite() || aspect_ratio <= 0.0 {
+        return *tablet;
+    }
 
-    #[test]
-    fn wider_window_expands_height() {
-        // Window 1920x1080 (16:9), tablet 4:3 (1.333...)
-        // Window is wider than tablet, so height expands to cover width
-        let area = fit_to_window(0, 0, 1920, 1080, 4.0 / 3.0).unwrap();
-        assert!((area.width - 1920.0).abs() < 0.01); // matches window width
-        assert!((area.height - 1440.0).abs() < 0.01); // extends beyond window height
-        assert!((area.center_x - 960.0).abs() < 0.01);
-        assert!((area.center_y - 540.0).abs() < 0.01);
+    let full = tablet.rect.size;
+    let size = if full.aspect_ratio() > aspect_ratio {
+        // Tablet is wider than the window — trim the sides
+        Size::new(full.height * aspect_ratio, full.height)
+    } else {
+        // Tablet is taller than the window — trim top and bottom
+        Size::new(full.width, full.width / aspect_ratio)
+    };
+
+    // Offset of the cropped center from the original center, in the area's
+    // frame, then rotated into tablet coordinates
+    let (fx, fy) = anchor.factors();
+    let dx = (fx - 0.5) * (full.width - size.width);
+    let dy = (fy - 0.5) * (full.height - size.height);
+    let (sin, cos) = tablet.rotation.to_radians().sin_cos();
+    let center = tablet.rect.center;
+
+    TabletArea {
+        rect: Rect::new(
+            Point::new(
+                center.x + dx * cos - dy * sin,
+                center.y + dx * sin + dy * cos,
+            ),
+            size,
+        ),
+        rotation: tablet.rotation,
     }
+}
 
-    #[test]
-    fn taller_window_expands_width() {
-        // Window 800x1200, tablet 1.6 (16:10)
-        // Window is taller than tablet, so width expands to cover height
-        let area = fit_to_window(100, 200, 800, 1200, 1.6).unwrap();
-        assert!((area.width - 1920.0).abs() < 0.01); // extends beyond window width
-        assert!((area.height - 1200.0).abs() < 0.01); // matches window height
-        assert!((area.center_x - 500.0).abs() < 0.01);
-        assert!((area.center_y - 800.0).abs() < 0.01);
+/// What the tablet is mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MappingMode {
+    /// Fit the tablet to the window (see [`FitMode`]).
+    #[default]
+    Window,
+    /// Map at a fixed physical scale centered on the window, independent of
+    /// its size (see [`physical_area`]).
+    Physical,
+    /// Map a fixed-size area that follows the cursor (see [`follow_cursor`]).
+    Cursor,
+    /// Map an area of a fixed pixel size centered on the window (see
+    /// [`fixed_area`]).
+    Fixed,
+}
+
+impl std::str::FromStr for MappingMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "window" => Ok(Self::Window),
+            "physical" => Ok(Self::Physical),
+            "cursor" => Ok(Self::Cursor),
+            "fixed" => Ok(Self::Fixed),
+            _ => Err(format!(
+                "'{s}' is not a mapping mode (expected window, physical, cursor, or fixed)"
+            )),
+        }
     }
+}
 
-    #[test]
-    fn exact_aspect_ratio_match() {
-        let area = fit_to_window(0, 0, 1600, 1000, 1.6).unwrap();
-        assert!((area.width - 1600.0).abs() < 0.01);
-        assert!((area.height - 1000.0).abs() < 0.01);
+/// What the follow-cursor area moves within.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Follow {
+    /// The monitor the cursor is on.
+    #[default]
+    Monitor,
+    /// The target window, so the area is a viewport that moves with it.
+    Window,
+}
+
+impl std::str::FromStr for Follow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "monitor" => Ok(Self::Monitor),
+            "window" => Ok(Self::Window),
+            _ => Err(format!(
+                "'{s}' is not a follow region (expected monitor or window)"
+            )),
+        }
     }
+}
 
-    #[test]
-    fn zero_dimensions_returns_none() {
-        assert!(fit_to_window(0, 0, 0, 100, 1.6).is_none());
-        assert!(fit_to_window(0, 0, 100, 0, 1.6).is_none());
-        assert!(fit_to_window(0, 0, -10, 100, 1.6).is_none());
+/// Monitor pixel density in pixels per millimetre along each axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelDensity {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl PixelDensity {
+    /// Convert dots per inch into pixels per millimetre.
+    pub fn from_dpi(dpi_x: f64, dpi_y: f64) -> Self {
+        PixelDensity {
+            x: dpi_x / 25.4,
+            y: dpi_y / 25.4,
+        }
     }
+}
 
-    #[test]
-    fn small_window() {
-        // 10x10 window, tablet 1.6 ratio → width expands to cover height
-        let area = fit_to_window(500, 300, 10, 10, 1.6).unwrap();
-        assert!((area.width - 16.0).abs() < 0.01); // 10 * 1.6
-        assert!((area.height - 10.0).abs() < 0.01);
+/// Precision zoom: the whole tablet maps to a fraction of the normal area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zoom {
+    /// Fraction of the normal area's width and height the tablet covers
+    /// (0.25 maps the tablet to a quarter of the width and height).
+    pub factor: f64,
+    /// Focus point as fractions of the window's width and height, so it
+    /// follows the window as it moves and resizes.
+    pub focus_x: f64,
+    pub focus_y: f64,
+}
+
+impl Zoom {
+    /// A zoom by `factor` focused on `point`, relative to `frame` (the rect
+    /// the zoom is later applied within, see [`zoom_area`]).
+    pub fn focused_on(factor: f64, frame: IntRect, point: Point<Px>) -> Zoom {
+        Zoom {
+            factor,
+            focus_x: ((point.x - frame.left as f64) / frame.width() as f64).clamp(0.0, 1.0),
+            focus_y: ((point.y - frame.top as f64) / frame.height() as f64).clamp(0.0, 1.0),
+        }
     }
+}
 
-    #[test]
-    fn offset_window_centers_correctly() {
-        // Window at (100, 200) with size 400x400, tablet 2:1
-        // Square window, tablet wider → width matches, height expands
-        let area = fit_to_window(100, 200, 400, 400, 2.0).unwrap();
-        assert!((area.width - 800.0).abs() < 0.01); // 400 * 2.0
-        assert!((area.height - 400.0).abs() < 0.01);
-        assert!((area.center_x - 300.0).abs() < 0.01); // 100 + 200
-        assert!((area.center_y - 400.0).abs() < 0.01); // 200 + 200
+/// How the tablet's aspect ratio is reconciled with the window's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FitMode {
+    /// Smallest area that covers the whole window. Part of the tablet maps
+    /// outside the window along one axis.
+    #[default]
+    Cover,
+    /// Largest area that fits inside the window (letterbox). The whole tablet
+    /// lands on the window, but a strip of the window is unreachable.
+    Contain,
+    /// Area equals the window exactly. The aspect ratio is not preserved.
+    Stretch,
+    /// Area equals the window exactly, and the tablet area is cropped to the
+    /// window's aspect ratio instead (see [`crop_tablet_area`]).
+    Crop,
+}
+
+impl std::str::FromStr for FitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cover" => Ok(Self::Cover),
+            "contain" | "letterbox" => Ok(Self::Contain),
+            "stretch" => Ok(Self::Stretch),
+            "crop" => Ok(Self::Crop),
+            _ => Err(format!(
+                "'{s}' is not a fit mode (expected cover, contain, stretch, or crop)"
+            )),
+        }
+    }
+}
+
+/// Where a shrunk or enlarged rectangle sits relative to the one it was
+/// derived from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Horizontal and vertical position as fractions: 0.0 is the left/top
+    /// edge, 0.5 the center, 1.0 the right/bottom edge.
+    fn factors(self) -> (f64, f64) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+impl std::str::FromStr for Anchor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "top-left" => Ok(Anchor::TopLeft),
+            "top" => Ok(Anchor::Top),
+            "top-right" => Ok(Anchor::TopRight),
+            "left" => Ok(Anchor::Left),
+            "center" | "centre" => Ok(Anchor::Center),
+            "right" => Ok(Anchor::Right),
+            "bottom-left" => Ok(Anchor::BottomLeft),
+            "bottom" => Ok(Anchor::Bottom),
+            "bottom-right" => Ok(Anchor::BottomRight),
+            _ => Err(format!(
+                "'{s}' is not an anchor (expected center, top, bottom, left, right, \
+                 top-left, top-right, bottom-left, or bottom-right)"
+            )),
+        }
+    }
+}
+
+/// Distance to trim from one edge of the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Inset {
+    /// Absolute distance in pixels.
+    Pixels(f64),
+    /// Percentage of the window's width (left/right) or height (top/bottom).
+    Percent(f64),
+}
+
+impl Default for Inset {
+    fn default() -> Self {
+        Inset::Pixels(0.0)
+    }
+}
+
+impl Inset {
+    fn resolve(self, extent: f64) -> f64 {
+        match self {
+            Inset::Pixels(px) => px,
+            Inset::Percent(pct) => extent * pct / 100.0,
+        }
+    }
+}
+
+impl std::str::FromStr for Inset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (v, percent) = parse_length(s, "px")?;
+        Ok(if percent {
+            Inset::Percent(v)
+        } else {
+            Inset::Pixels(v)
+        })
+    }
+}
+
+/// Per-edge insets trimmed from the window before fitting, so toolbars and
+/// docks inside the window bounds are excluded from the mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Insets {
+    pub left: Inset,
+    pub top: Inset,
+    pub right: Inset,
+    pub bottom: Inset,
+}
+
+impl std::str::FromStr for Insets {
+    type Err = String;
+
+    /// Parses `ALL` or `LEFT,TOP,RIGHT,BOTTOM`, where each value is pixels
+    /// (`40`, `40px`) or a percentage of the window (`5%`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Inset>, _>>()?;
+        match values[..] {
+            [all] => Ok(Insets {
+                left: all,
+                top: all,
+                right: all,
+                bottom: all,
+            }),
+            [left, top, right, bottom] => Ok(Insets {
+                left,
+                top,
+                right,
+                bottom,
+            }),
+            _ => Err(format!(
+                "'{s}' must be one value or four values (left,top,right,bottom)"
+            )),
+        }
+    }
+}
+
+/// A sub-rectangle of the window in fractions of its width and height, so it
+/// follows the window as it moves and resizes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for Region {
+    /// The whole window.
+    fn default() -> Self {
+        Region {
+            left: 0.0,
+            top: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+impl std::str::FromStr for Region {
+    type Err = String;
+
+    /// Parses `LEFT,TOP,WIDTH,HEIGHT`, each a fraction of the window between
+    /// 0 and 1 (`0.2,0.1,0.6,0.8`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(',')
+            .map(|v| {
+                v.trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("'{v}' is not a valid fraction"))
+            })
+            .collect::<Result<Vec<f64>, _>>()?;
+        let [left, top, width, height] = values[..] else {
+            return Err(format!("'{s}' must be four values (left,top,width,height)"));
+        };
+        if !values.iter().all(|v| (0.0..=1.0).contains(v)) {
+            return Err(format!("region '{s}' values must be between 0 and 1"));
+        }
+        if width == 0.0 || height == 0.0 {
+            return Err(format!("region '{s}' must have a non-zero size"));
+        }
+        if left + width > 1.0 + 1e-9 || top + height > 1.0 + 1e-9 {
+            return Err(format!("region '{s}' extends past the window"));
+        }
+        Ok(Region {
+            left,
+            top,
+            width,
+            height,
+        })
+    }
+}
+
+/// Bounds the display area is kept inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Clamp {
+    /// The area may extend anywhere, including off-screen.
+    #[default]
+    None,
+    /// Keep the area on the monitor that holds the window.
+    Monitor,
+    /// Keep the area on the virtual desktop spanning all monitors.
+    Desktop,
+}
+
+impl std::str::FromStr for Clamp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Clamp::None),
+            "monitor" => Ok(Clamp::Monitor),
+            "desktop" => Ok(Clamp::Desktop),
+            _ => Err(format!(
+                "'{s}' is not a clamp mode (expected none, monitor, or desktop)"
+            )),
+        }
+    }
+}
+
+/// A lower or upper bound on the display area's size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeLimit {
+    /// Absolute width and height in pixels.
+    Pixels(Size<Px>),
+    /// Percentage of the monitor's width and height.
+    Percent(f64),
+}
+
+impl SizeLimit {
+    /// The limit in pixels on `monitor`.
+    pub fn resolve(self, monitor: IntRect) -> Size<Px> {
+        match self {
+            SizeLimit::Pixels(size) => size,
+            SizeLimit::Percent(pct) => Size::new(
+                monitor.width() as f64 * pct / 100.0,
+                monitor.height() as f64 * pct / 100.0,
+            ),
+        }
+    }
+}
+
+impl std::str::FromStr for SizeLimit {
+    type Err = String;
+
+    /// Parses `WIDTHxHEIGHT` in pixels (`800x500`) or a percentage of the
+    /// monitor (`30%`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.trim().ends_with('%') {
+            return s.parse().map(SizeLimit::Pixels);
+        }
+        let (v, _) = parse_length(s, "%")?;
+        if v == 0.0 {
+            return Err(format!("size limit '{s}' must be greater than 0%"));
+        }
+        Ok(SizeLimit::Percent(v))
+    }
+}
+
+/// Shrink the window by the given insets.
+///
+/// Returns `None` if the insets leave zero or negative dimensions.
+pub fn apply_insets(window: IntRect, insets: &Insets) -> Option<IntRect> {
+    let w = window.width() as f64;
+    let h = window.height() as f64;
+
+    let inner = IntRect {
+        left: window.left + insets.left.resolve(w).round() as i32,
+        top: window.top + insets.top.resolve(h).round() as i32,
+        right: window.right - insets.right.resolve(w).round() as i32,
+        bottom: window.bottom - insets.bottom.resolve(h).round() as i32,
+    };
+    (!inner.is_empty()).then_some(inner)
+}
+
+/// Select the part of the window covered by `region`, rounded to whole
+/// pixels.
+///
+/// Returns `None` if the region rounds to zero or negative dimensions.
+pub fn apply_region(window: IntRect, region: &Region) -> Option<IntRect> {
+    let w = window.width() as f64;
+    let h = window.height() as f64;
+
+    let inner = IntRect {
+        left: window.left + (region.left * w).round() as i32,
+        top: window.top + (region.top * h).round() as i32,
+        right: window.left + ((region.left + region.width) * w).round() as i32,
+        bottom: window.top + ((region.top + region.height) * h).round() as i32,
+    };
+    (!inner.is_empty()).then_some(inner)
+}
+
+/// A display area fitted to a window by [`fit_to_window`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub area: DisplayArea,
+    /// How far the tablet's aspect ratio was bent to fit, as a fraction (see
+    /// [`DisplayArea::distortion`]).
+    pub distortion: f64,
+}
+
+/// Compute the display area for the given window according to `mode`,
+/// centered on the window.
+///
+/// With [`FitMode::Cover`] the pen can reach every part of the window; the
+/// mapped area may extend beyond the window in one direction to maintain the
+/// tablet's aspect ratio. With [`FitMode::Contain`] the area matches the
+/// window along one axis and is letterboxed along the other. With
+/// [`FitMode::Stretch`] the area is the window itself.
+///
+/// `max_distortion` lets cover and contain bend the aspect ratio towards the
+/// window's by up to that fraction (0.1 for 10%) before any overflow or
+/// letterbox is added. A window within the tolerance is mapped exactly. The
+/// result reports how much was applied.
+///
+/// Returns `None` if the window has zero or negative dimensions, or the
+/// tablet has no usable aspect ratio (an empty or invalid area).
+pub fn fit_to_window(
+    window: IntRect,
+    tablet_aspect_ratio: f64,
+    mode: FitMode,
+    max_distortion: f64,
+) -> Option<Fit> {
+    if window.is_empty() || !(tablet_aspect_ratio.is_finite() && tablet_aspect_ratio > 0.0) {
+        return None;
+    }
+
+    let window = window.to_rect();
+    let w = window.width();
+    let h = window.height();
+    let window_aspect = w / h;
+
+    // Bend the tablet's aspect ratio towards the window's, within tolerance
+    let limit = 1.0 + max_distortion.max(0.0);
+    let aspect = window_aspect.clamp(tablet_aspect_ratio / limit, tablet_aspect_ratio * limit);
+
+    let (fit_w, fit_h) = match mode {
+        FitMode::Cover if window_aspect > aspect => {
+            // Window is wider than tablet ratio — expand height to cover width
+            (w, w / aspect)
+        }
+        FitMode::Cover => {
+            // Window is taller than tablet ratio — expand width to cover height
+            (h * aspect, h)
+        }
+        FitMode::Contain if window_aspect > aspect => {
+            // Window is wider than tablet ratio — match height, shrink width
+            (h * aspect, h)
+        }
+        FitMode::Contain => {
+            // Window is taller than tablet ratio — match width, shrink height
+            (w, w / aspect)
+        }
+        FitMode::Stretch | FitMode::Crop => (w, h),
+    };
+
+    let area = Rect::new(window.center, Size::new(fit_w, fit_h));
+    let distortion = match mode {
+        // The tablet area is cropped to the window's shape instead
+        FitMode::Crop => 0.0,
+        _ => area.distortion(tablet_aspect_ratio),
+    };
+    Some(Fit { area, distortion })
+}
+
+/// Position `area` relative to the window so the edges named by `anchor`
+/// line up, keeping its size.
+///
+/// Where the area is larger than the window (cover), the anchor decides
+/// where the overflow goes: with [`Anchor::Top`] the area's top edge matches
+/// the window's and all vertical overflow is below. Where it is smaller
+/// (contain), the anchor decides which side the letterbox strip is on.
+/// [`Anchor::Center`] splits either evenly, like [`fit_to_window`] does.
+pub fn anchor_to_window(area: &DisplayArea, window: IntRect, anchor: Anchor) -> DisplayArea {
+    let (fx, fy) = anchor.factors();
+    let center = Point::new(
+        window.left as f64 + fx * window.width() as f64 + (0.5 - fx) * area.width(),
+        window.top as f64 + fy * window.height() as f64 + (0.5 - fy) * area.height(),
+    );
+    Rect::new(center, area.size)
+}
+
+/// Compute a display area at a fixed physical scale, centered on the window.
+///
+/// One millimetre of pen travel on `tablet` moves the cursor `scale`
+/// millimetres on a monitor with the given pixel density, whatever the
+/// window's size. The window only provides the center.
+///
+/// Returns `None` if the window has zero or negative dimensions.
+pub fn physical_area(
+    window: IntRect,
+    tablet: &TabletArea,
+    density: PixelDensity,
+    scale: f64,
+) -> Option<DisplayArea> {
+    if window.is_empty() {
+        return None;
+    }
+
+    let tablet = tablet.rect.size;
+    Some(Rect::new(
+        window.to_rect().center,
+        Size::new(
+            tablet.width * density.x * scale,
+            tablet.height * density.y * scale,
+        ),
+    ))
+}
+
+/// Compute a display area of a fixed `size`, centered on the window.
+///
+/// Only the window's position matters: resizing the window leaves the area's
+/// size, and therefore the pen feel, unchanged.
+///
+/// Returns `None` if the window has zero or negative dimensions.
+pub fn fixed_area(window: IntRect, size: Size<Px>) -> Option<DisplayArea> {
+    if window.is_empty() {
+        return None;
+    }
+    Some(Rect::new(window.to_rect().center, size))
+}
+
+/// Scale `area` up to at least `min` and down to at most `max` along both
+/// axes, keeping its aspect ratio, and center it on the window.
+///
+/// The maximum wins if the limits conflict. An area already within its
+/// limits is returned unchanged.
+pub fn limit_size(
+    area: &DisplayArea,
+    window: IntRect,
+    min: Option<Size<Px>>,
+    max: Option<Size<Px>>,
+) -> DisplayArea {
+    let mut factor: f64 = 1.0;
+    if let Some(min) = min {
+        factor = factor
+            .max(min.width / area.width())
+            .max(min.height / area.height());
+    }
+    if let Some(max) = max {
+        factor = factor
+            .min(max.width / area.width())
+            .min(max.height / area.height());
+    }
+    if factor == 1.0 || !factor.is_finite() {
+        return *area;
+    }
+    Rect::new(
+        window.to_rect().center,
+        Size::new(area.width() * factor, area.height() * factor),
+    )
+}
+
+/// Shrink `area` by the zoom factor and center it on the zoom's focus point
+/// within the window.
+///
+/// The zoomed area is kept inside `area`, so zooming near an edge shifts the
+/// focus inward rather than mapping past the normal area.
+pub fn zoom_area(area: &DisplayArea, window: IntRect, zoom: &Zoom) -> DisplayArea {
+    let factor = zoom.factor.clamp(f64::EPSILON, 1.0);
+    let size = Size::new(area.width() * factor, area.height() * factor);
+    let focus_x = window.left as f64 + window.width() as f64 * zoom.focus_x;
+    let focus_y = window.top as f64 + window.height() as f64 * zoom.focus_y;
+
+    // Half the zoomed size in from each edge keeps it inside `area`
+    let (half_w, half_h) = (size.width / 2.0, size.height / 2.0);
+    Rect::new(
+        Point::new(
+            focus_x.clamp(area.left() + half_w, area.right() - half_w),
+            focus_y.clamp(area.top() + half_h, area.bottom() - half_h),
+        ),
+        size,
+    )
+}
+
+/// Move `area` so it lies inside `bounds`, keeping its size (and therefore
+/// its aspect ratio).
+///
+/// Along an axis where the area is larger than the bounds, it is centered on
+/// the bounds so the overflow is split evenly.
+pub fn clamp_to_bounds(area: &DisplayArea, bounds: IntRect) -> DisplayArea {
+    fn clamp_axis(center: f64, size: f64, start: f64, extent: f64) -> f64 {
+        if size >= extent {
+            start + extent / 2.0
+        } else {
+            center.clamp(start + size / 2.0, start + extent - size / 2.0)
+        }
+    }
+
+    let center = Point::new(
+        clamp_axis(
+            area.center.x,
+            area.width(),
+            bounds.left as f64,
+            bounds.width() as f64,
+        ),
+        clamp_axis(
+            area.center.y,
+            area.height(),
+            bounds.top as f64,
+            bounds.height() as f64,
+        ),
+    );
+    Rect::new(center, area.size)
+}
+
+/// Mapping between Win32 screen coordinates and OTD display coordinates.
+///
+/// Screen coordinates have their origin at the primary monitor's top-left
+/// corner, so monitors left of or above the primary have negative
+/// coordinates. OTD's display area is measured from the top-left corner of
+/// the virtual desktop (the bounding box of all monitors) instead, so every
+/// coordinate is non-negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DesktopSpace {
+    /// Screen coordinates of the virtual desktop's top-left corner.
+    pub origin_x: i32,
+    pub origin_y: i32,
+}
+
+impl DesktopSpace {
+    /// Derive the virtual desktop origin from monitor bounds in screen
+    /// coordinates.
+    pub fn from_monitors(monitors: &[IntRect]) -> Self {
+        DesktopSpace {
+            origin_x: monitors.iter().map(|m| m.left).min().unwrap_or(0),
+            origin_y: monitors.iter().map(|m| m.top).min().unwrap_or(0),
+        }
+    }
+
+    /// Convert an area in screen coordinates to OTD display coordinates.
+    pub fn to_otd(self, area: &DisplayArea) -> DisplayArea {
+        let center = Point::new(
+            area.center.x - self.origin_x as f64,
+            area.center.y - self.origin_y as f64,
+        );
+        Rect::new(center, area.size)
+    }
+
+    /// Convert an area in OTD display coordinates to screen coordinates.
+    pub fn to_screen(self, area: &DisplayArea) -> DisplayArea {
+        let center = Point::new(
+            area.center.x + self.origin_x as f64,
+            area.center.y + self.origin_y as f64,
+        );
+        Rect::new(center, area.size)
+    }
+}
+
+/// Compute a fixed-size display area that follows the cursor within
+/// `container`.
+///
+/// The area has the tablet's aspect ratio and is `scale` times the largest
+/// such area that fits the container. It stays put while the cursor is inside
+/// it — the pen moves the cursor too, so chasing it would pull the area out
+/// from under the pen — and is re-centered on the cursor (kept inside the
+/// container) once the cursor leaves it. `previous` is the area currently
+/// mapped, if any.
+///
+/// Returns `None` if the container has zero or negative dimensions.
+pub fn follow_cursor(
+    previous: Option<&DisplayArea>,
+    container: IntRect,
+    tablet_aspect_ratio: f64,
+    scale: f64,
+    cursor: Point<Px>,
+) -> Option<DisplayArea> {
+    let full = fit_to_window(container, tablet_aspect_ratio, FitMode::Contain, 0.0)?.area;
+    let size = Size::new(full.width() * scale, full.height() * scale);
+
+    let area = match previous {
+        Some(previous) if previous.size == size && previous.contains(cursor) => *previous,
+        _ => Rect::new(cursor, size),
+    };
+    Some(clamp_to_bounds(&area, container))
+}
+
+/// Width and height of the strip on each side of the window that the display
+/// area leaves uncovered, as produced by [`FitMode::Contain`].
+///
+/// Returns a zero size when the area covers the window along both axes.
+pub fn letterbox(window: IntRect, area: &DisplayArea) -> Size<Px> {
+    Size::new(
+        (window.width() as f64 - area.width()).max(0.0) / 2.0,
+        (window.height() as f64 - area.height()).max(0.0) / 2.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: i32, top: i32, width: i32, height: i32) -> IntRect {
+        IntRect::from_size(left, top, width, height)
+    }
+
+    #[test]
+    fn wider_window_expands_height() {
+        // Window 1920x1080 (16:9), tablet 4:3 (1.333...)
+        // Window is wider than tablet, so height expands to cover width
+        let area = fit_to_window(rect(0, 0, 1920, 1080), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 1920.0).abs() < 0.01); // matches window width
+        assert!((area.height() - 1440.0).abs() < 0.01); // extends beyond window height
+        assert!((area.center.x - 960.0).abs() < 0.01);
+        assert!((area.center.y - 540.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn taller_window_expands_width() {
+        // Window 800x1200, tablet 1.6 (16:10)
+        // Window is taller than tablet, so width expands to cover height
+        let area = fit_to_window(rect(100, 200, 800, 1200), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 1920.0).abs() < 0.01); // extends beyond window width
+        assert!((area.height() - 1200.0).abs() < 0.01); // matches window height
+        assert!((area.center.x - 500.0).abs() < 0.01);
+        assert!((area.center.y - 800.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn exact_aspect_ratio_match() {
+        let area = fit_to_window(rect(0, 0, 1600, 1000), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 1600.0).abs() < 0.01);
+        assert!((area.height() - 1000.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn zero_dimensions_returns_none() {
+        assert!(fit_to_window(rect(0, 0, 0, 100), 1.6, FitMode::Cover, 0.0).is_none());
+        assert!(fit_to_window(rect(0, 0, 100, 0), 1.6, FitMode::Cover, 0.0).is_none());
+        assert!(fit_to_window(rect(0, 0, -10, 100), 1.6, FitMode::Cover, 0.0).is_none());
+    }
+
+    #[test]
+    fn small_window() {
+        // 10x10 window, tablet 1.6 ratio → width expands to cover height
+        let area = fit_to_window(rect(500, 300, 10, 10), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 16.0).abs() < 0.01); // 10 * 1.6
+        assert!((area.height() - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn offset_window_centers_correctly() {
+        // Window at (100, 200) with size 400x400, tablet 2:1
+        // Square window, tablet wider → width matches, height expands
+        let area = fit_to_window(rect(100, 200, 400, 400), 2.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 800.0).abs() < 0.01); // 400 * 2.0
+        assert!((area.height() - 400.0).abs() < 0.01);
+        assert!((area.center.x - 300.0).abs() < 0.01); // 100 + 200
+        assert!((area.center.y - 400.0).abs() < 0.01); // 200 + 200
+    }
+
+    #[test]
+    fn contain_wider_window_shrinks_width() {
+        // Window 1920x1080 (16:9), tablet 4:3
+        // Window is wider than tablet, so the area matches height and is
+        // letterboxed horizontally
+        let area = fit_to_window(rect(0, 0, 1920, 1080), 4.0 / 3.0, FitMode::Contain, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 1440.0).abs() < 0.01); // 1080 * 4/3
+        assert!((area.height() - 1080.0).abs() < 0.01); // matches window height
+        assert!((area.center.x - 960.0).abs() < 0.01);
+        assert!((area.center.y - 540.0).abs() < 0.01);
+
+        let strip = letterbox(rect(0, 0, 1920, 1080), &area);
+        assert!((strip.width - 240.0).abs() < 0.01); // (1920 - 1440) / 2
+        assert!(strip.height.abs() < 0.01);
+    }
+
+    #[test]
+    fn contain_taller_window_shrinks_height() {
+        // Window 800x1200, tablet 1.6 (16:10)
+        // Window is taller than tablet, so the area matches width and is
+        // letterboxed vertically
+        let area = fit_to_window(rect(100, 200, 800, 1200), 1.6, FitMode::Contain, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 800.0).abs() < 0.01); // matches window width
+        assert!((area.height() - 500.0).abs() < 0.01); // 800 / 1.6
+        assert!((area.center.x - 500.0).abs() < 0.01);
+        assert!((area.center.y - 800.0).abs() < 0.01);
+
+        let strip = letterbox(rect(0, 0, 800, 1200), &area);
+        assert!(strip.width.abs() < 0.01);
+        assert!((strip.height - 350.0).abs() < 0.01); // (1200 - 500) / 2
+    }
+
+    #[test]
+    fn contain_exact_aspect_ratio_match() {
+        let area = fit_to_window(rect(0, 0, 1600, 1000), 1.6, FitMode::Contain, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 1600.0).abs() < 0.01);
+        assert!((area.height() - 1000.0).abs() < 0.01);
+        assert_eq!(
+            letterbox(rect(0, 0, 1600, 1000), &area),
+            Size::new(0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn stretch_matches_window_exactly() {
+        // Aspect ratio is ignored — the area is the window
+        let area = fit_to_window(rect(100, 200, 800, 1200), 1.6, FitMode::Stretch, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 800.0).abs() < 0.01);
+        assert!((area.height() - 1200.0).abs() < 0.01);
+        assert!((area.center.x - 500.0).abs() < 0.01);
+        assert!((area.center.y - 800.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn cover_leaves_no_letterbox() {
+        let area = fit_to_window(rect(0, 0, 1920, 1080), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert_eq!(
+            letterbox(rect(0, 0, 1920, 1080), &area),
+            Size::new(0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn zero_dimensions_returns_none_in_every_mode() {
+        for mode in [FitMode::Cover, FitMode::Contain, FitMode::Stretch] {
+            assert!(fit_to_window(rect(0, 0, 0, 100), 1.6, mode, 0.0).is_none());
+            assert!(fit_to_window(rect(0, 0, 100, -5), 1.6, mode, 0.0).is_none());
+        }
+    }
+
+    #[test]
+    fn invalid_tablet_aspect_returns_none() {
+        // An empty tablet area gives 0/0 or x/0
+        for aspect in [f64::NAN, f64::INFINITY, 0.0, -1.6] {
+            for mode in [FitMode::Cover, FitMode::Contain, FitMode::Stretch] {
+                assert!(fit_to_window(rect(0, 0, 800, 600), aspect, mode, 0.1).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn validate_rejects_nan_and_empty_size() {
+        let area: DisplayArea = Rect::new(Point::new(960.0, 540.0), Size::new(1920.0, 1080.0));
+        assert_eq!(area.validate(), Ok(area));
+        assert!(
+            Rect::<Px>::new(Point::new(f64::NAN, 540.0), Size::new(1920.0, 1080.0))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            Rect::<Mm>::new(Point::new(80.0, 50.0), Size::new(-160.0, 100.0))
+                .validate()
+                .is_err()
+        );
+        // A zero-sized tablet area has no aspect ratio to fit
+        for size in [Size::new(0.0, 100.0), Size::new(160.0, 0.0)] {
+            assert!(
+                Rect::<Mm>::new(Point::new(80.0, 50.0), size)
+                    .validate()
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_int_rect_and_point() {
+        assert_eq!("-1920,0,1920,1080".parse(), Ok(rect(-1920, 0, 1920, 1080)));
+        assert!("0,0,1920".parse::<IntRect>().is_err());
+        assert!("0,0,0,1080".parse::<IntRect>().is_err());
+        assert!("0,0,1.5,1080".parse::<IntRect>().is_err());
+        assert_eq!("-500, 300".parse(), Ok(Point::<Px>::new(-500.0, 300.0)));
+        assert!("500".parse::<Point<Px>>().is_err());
+    }
+
+    #[test]
+    fn int_rect_round_trips_to_center_form() {
+        let rect = IntRect::from_size(-1920, 100, 800, 600);
+        assert_eq!((rect.right, rect.bottom), (-1120, 700));
+        let area = rect.to_rect();
+        assert_eq!(
+            (area.left(), area.top(), area.right(), area.bottom()),
+            (-1920.0, 100.0, -1120.0, 700.0)
+        );
+        assert!(IntRect::from_size(0, 0, 0, 10).is_empty());
+    }
+
+    #[test]
+    fn parse_fit_mode() {
+        assert_eq!("cover".parse(), Ok(FitMode::Cover));
+        assert_eq!("Contain".parse(), Ok(FitMode::Contain));
+        assert_eq!("letterbox".parse(), Ok(FitMode::Contain));
+        assert_eq!("STRETCH".parse(), Ok(FitMode::Stretch));
+        assert!("fill".parse::<FitMode>().is_err());
+    }
+
+    #[test]
+    fn no_insets_keep_window() {
+        let inner = apply_insets(rect(100, 200, 800, 600), &Insets::default());
+        assert_eq!(inner, Some(rect(100, 200, 800, 600)));
+    }
+
+    #[test]
+    fn pixel_insets_trim_each_edge() {
+        // Toolbar on top (60px), docker on the right (300px)
+        let insets: Insets = "0,60,300,0".parse().unwrap();
+        let inner = apply_insets(rect(100, 200, 1600, 900), &insets);
+        assert_eq!(inner, Some(rect(100, 260, 1300, 840)));
+    }
+
+    #[test]
+    fn percent_insets_scale_with_window() {
+        // 10% of 1000 wide, 5% of 800 tall
+        let insets: Insets = "10%,5%,10%,5%".parse().unwrap();
+        let inner = apply_insets(rect(0, 0, 1000, 800), &insets);
+        assert_eq!(inner, Some(rect(100, 40, 800, 720)));
+    }
+
+    #[test]
+    fn single_inset_applies_to_all_edges() {
+        let insets: Insets = "20px".parse().unwrap();
+        assert_eq!(insets.left, Inset::Pixels(20.0));
+        assert_eq!(insets.bottom, Inset::Pixels(20.0));
+        let inner = apply_insets(rect(-1920, 0, 1920, 1080), &insets);
+        assert_eq!(inner, Some(rect(-1900, 20, 1880, 1040)));
+    }
+
+    #[test]
+    fn insets_consuming_window_return_none() {
+        let insets: Insets = "50%,0,50%,0".parse().unwrap();
+        assert!(apply_insets(rect(0, 0, 800, 600), &insets).is_none());
+        let insets: Insets = "0,400,0,300".parse().unwrap();
+        assert!(apply_insets(rect(0, 0, 800, 600), &insets).is_none());
+    }
+
+    #[test]
+    fn default_region_is_whole_window() {
+        let window = rect(-1920, 100, 1600, 900);
+        assert_eq!(apply_region(window, &Region::default()), Some(window));
+    }
+
+    #[test]
+    fn region_follows_window_as_it_moves_and_resizes() {
+        let region: Region = "0.2,0.1,0.6,0.8".parse().unwrap();
+        assert_eq!(
+            apply_region(rect(0, 0, 1000, 500), &region),
+            Some(rect(200, 50, 600, 400))
+        );
+        // Same fractions after moving and doubling the window
+        assert_eq!(
+            apply_region(rect(-2000, 300, 2000, 1000), &region),
+            Some(rect(-1600, 400, 1200, 800))
+        );
+    }
+
+    #[test]
+    fn region_composes_with_fit() {
+        // Right half of a 1600x1000 window is 800x1000; a 1.6 tablet covers
+        // its width and overflows vertically
+        let region: Region = "0.5,0,0.5,1".parse().unwrap();
+        let canvas = apply_region(rect(0, 0, 1600, 1000), &region).unwrap();
+        let area = fit_to_window(canvas, 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 1600.0).abs() < 0.01);
+        assert!((area.height() - 1000.0).abs() < 0.01);
+        assert!((area.center.x - 1200.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn tiny_region_of_small_window_returns_none() {
+        let region: Region = "0.5,0.5,0.01,0.01".parse().unwrap();
+        assert!(apply_region(rect(0, 0, 40, 40), &region).is_none());
+    }
+
+    #[test]
+    fn invalid_region_rejected() {
+        assert!("0.2,0.1,0.6".parse::<Region>().is_err());
+        assert!("0.5,0,0.6,1".parse::<Region>().is_err()); // past the right edge
+        assert!("0,0,0,1".parse::<Region>().is_err());
+        assert!("-0.1,0,0.5,0.5".parse::<Region>().is_err());
+        assert!("20%,0,0.5,0.5".parse::<Region>().is_err());
+    }
+
+    #[test]
+    fn invalid_insets_rejected() {
+        assert!("1,2".parse::<Insets>().is_err());
+        assert!("-5".parse::<Insets>().is_err());
+        assert!("abc%".parse::<Insets>().is_err());
+    }
+
+    fn tablet(width: f64, height: f64, rotation: f64) -> TabletArea {
+        TabletArea {
+            rect: Rect::new(
+                Point::new(width / 2.0, height / 2.0),
+                Size::new(width, height),
+            ),
+            rotation,
+        }
+    }
+
+    #[test]
+    fn crop_mode_maps_window_exactly() {
+        let area = fit_to_window(rect(100, 200, 800, 1200), 1.6, FitMode::Crop, 0.0)
+            .unwrap()
+            .area;
+        assert!((area.width() - 800.0).abs() < 0.01);
+        assert!((area.height() - 1200.0).abs() < 0.01);
+        assert!((area.center.x - 500.0).abs() < 0.01);
+        assert!((area.center.y - 800.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn crop_tablet_to_square_window_centered() {
+        // 160x100mm tablet, square window → 100x100mm in the middle
+        let cropped = crop_tablet_area(&tablet(160.0, 100.0, 0.0), 1.0, Anchor::Center);
+        assert!((cropped.rect.width() - 100.0).abs() < 0.01);
+        assert!((cropped.rect.height() - 100.0).abs() < 0.01);
+        assert!((cropped.rect.center.x - 80.0).abs() < 0.01);
+        assert!((cropped.rect.center.y - 50.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn crop_tablet_anchored_to_edge() {
+        // Left anchor keeps the left edge at x=0
+        let cropped = crop_tablet_area(&tablet(160.0, 100.0, 0.0), 1.0, Anchor::Left);
+        assert!((cropped.rect.center.x - 50.0).abs() < 0.01);
+        assert!((cropped.rect.center.y - 50.0).abs() < 0.01);
+
+        // Wide window on the same tablet trims height; bottom anchor keeps
+        // the bottom edge at y=100
+        let cropped = crop_tablet_area(&tablet(160.0, 100.0, 0.0), 3.2, Anchor::Bottom);
+        assert!((cropped.rect.width() - 160.0).abs() < 0.01);
+        assert!((cropped.rect.height() - 50.0).abs() < 0.01);
+        assert!((cropped.rect.center.x - 80.0).abs() < 0.01);
+        assert!((cropped.rect.center.y - 75.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn crop_tablet_follows_rotation() {
+        // Rotated 90°, the area's left edge lies along the tablet's top
+        let cropped = crop_tablet_area(&tablet(160.0, 100.0, 90.0), 1.0, Anchor::Left);
+        assert!((cropped.rect.center.x - 80.0).abs() < 0.01);
+        assert!((cropped.rect.center.y - 20.0).abs() < 0.01); // 50 - 30
+        assert!((cropped.rotation - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn crop_tablet_matching_aspect_is_unchanged() {
+        let full = tablet(160.0, 100.0, 0.0);
+        assert_eq!(crop_tablet_area(&full, 1.6, Anchor::TopLeft), full);
+        assert_eq!(crop_tablet_area(&full, f64::NAN, Anchor::Center), full);
+    }
+
+    #[test]
+    fn tablet_region_in_millimetres() {
+        // Leave 20mm next to the express keys on the left of a 160x100 area
+        let region: TabletRegion = "20mm,0,140,100".parse().unwrap();
+        let active = restrict_tablet_area(&tablet(160.0, 100.0, 0.0), &region).unwrap();
+        assert!((active.rect.width() - 140.0).abs() < 1e-9);
+        assert!((active.rect.height() - 100.0).abs() < 1e-9);
+        assert!((active.rect.center.x - 90.0).abs() < 1e-9);
+        assert!((active.rect.center.y - 50.0).abs() < 1e-9);
+        assert!((active.rect.aspect_ratio() - 1.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tablet_region_in_percent() {
+        // Small wrist-only area in the middle
+        let region: TabletRegion = "25%,25%,50%,50%".parse().unwrap();
+        let active = restrict_tablet_area(&tablet(160.0, 100.0, 0.0), &region).unwrap();
+        assert!((active.rect.width() - 80.0).abs() < 1e-9);
+        assert!((active.rect.height() - 50.0).abs() < 1e-9);
+        assert!((active.rect.center.x - 80.0).abs() < 1e-9);
+        assert!((active.rect.center.y - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tablet_region_follows_area_rotation() {
+        // Area turned 180° for left-handed use: its left edge is on the
+        // tablet's right
+        let region: TabletRegion = "0,0,50%,100%".parse().unwrap();
+        let active = restrict_tablet_area(&tablet(160.0, 100.0, 180.0), &region).unwrap();
+        assert!((active.rect.center.x - 120.0).abs() < 1e-9);
+        assert!((active.rect.center.y - 50.0).abs() < 1e-9);
+        assert!((active.rotation - 180.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tablet_region_outside_area_is_rejected() {
+        let full = tablet(160.0, 100.0, 0.0);
+        let past_edge: TabletRegion = "40,0,140,100".parse().unwrap();
+        assert!(restrict_tablet_area(&full, &past_edge).is_none());
+        let empty: TabletRegion = "0,0,0%,100%".parse().unwrap();
+        assert!(restrict_tablet_area(&full, &empty).is_none());
+        assert!("0,0,100".parse::<TabletRegion>().is_err());
+        assert!("0,0,-5mm,100".parse::<TabletRegion>().is_err());
+    }
+
+    #[test]
+    fn parse_anchor() {
+        assert_eq!("center".parse(), Ok(Anchor::Center));
+        assert_eq!("Top-Left".parse(), Ok(Anchor::TopLeft));
+        assert_eq!("bottom_right".parse(), Ok(Anchor::BottomRight));
+        assert!("middle".parse::<Anchor>().is_err());
+    }
+
+    const MONITOR: IntRect = IntRect::from_size(0, 0, 1920, 1080);
+
+    fn assert_inside(area: &DisplayArea, bounds: IntRect) {
+        assert!(area.left() >= bounds.left as f64 - 0.01);
+        assert!(area.top() >= bounds.top as f64 - 0.01);
+        assert!(area.right() <= bounds.right as f64 + 0.01);
+        assert!(area.bottom() <= bounds.bottom as f64 + 0.01);
+    }
+
+    #[test]
+    fn clamp_keeps_area_inside_monitor_at_every_edge_and_corner() {
+        // 800x400 windows (2:1) with a 4:3 tablet → area 800x600, which
+        // overflows the window by 100px above and below
+        let windows = [
+            (0, 340, 800, 400),    // left edge
+            (1120, 340, 800, 400), // right edge
+            (560, 0, 800, 400),    // top edge
+            (560, 680, 800, 400),  // bottom edge
+            (0, 0, 800, 400),      // top-left corner
+            (1120, 0, 800, 400),   // top-right corner
+            (0, 680, 800, 400),    // bottom-left corner
+            (1120, 680, 800, 400), // bottom-right corner
+        ];
+
+        for (left, top, width, height) in windows {
+            let area = fit_to_window(
+                rect(left, top, width, height),
+                4.0 / 3.0,
+                FitMode::Cover,
+                0.0,
+            )
+            .unwrap()
+            .area;
+            let clamped = clamp_to_bounds(&area, MONITOR);
+            assert_inside(&clamped, MONITOR);
+            // Size and aspect ratio are untouched
+            assert!((clamped.width() - 800.0).abs() < 0.01);
+            assert!((clamped.height() - 600.0).abs() < 0.01);
+            // Only the overflowing axis moves
+            assert!((clamped.center.x - area.center.x).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn clamp_shifts_only_as_far_as_needed() {
+        // Window touching the top edge: area overflows 100px above
+        let area = fit_to_window(rect(560, 0, 800, 400), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let clamped = clamp_to_bounds(&area, MONITOR);
+        assert!((clamped.center.y - 300.0).abs() < 0.01); // top edge at 0
+
+        // Window in the middle is left alone
+        let area = fit_to_window(rect(560, 340, 800, 400), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert_eq!(clamp_to_bounds(&area, MONITOR), area);
+    }
+
+    #[test]
+    fn clamp_centers_area_larger_than_bounds() {
+        let area = Rect::new(Point::new(300.0, 540.0), Size::new(2400.0, 900.0));
+        let clamped = clamp_to_bounds(&area, MONITOR);
+        assert!((clamped.center.x - 960.0).abs() < 0.01);
+        assert!((clamped.center.y - 540.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn clamp_to_monitor_left_of_primary() {
+        let monitor = rect(-1920, 0, 1920, 1080);
+        let area = fit_to_window(rect(-1920, 0, 800, 400), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let clamped = clamp_to_bounds(&area, monitor);
+        assert_inside(&clamped, monitor);
+        assert!((clamped.center.x - -1520.0).abs() < 0.01);
+        assert!((clamped.center.y - 300.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn intersect_keeps_visible_part() {
+        // Window hanging off the right and bottom edges
+        assert_eq!(
+            rect(1500, 800, 800, 600).intersect(&MONITOR),
+            Some(rect(1500, 800, 420, 280))
+        );
+        // Window hanging off the left and top edges
+        assert_eq!(
+            rect(-200, -100, 800, 600).intersect(&MONITOR),
+            Some(rect(0, 0, 600, 500))
+        );
+        // Fully inside
+        assert_eq!(
+            rect(10, 20, 30, 40).intersect(&MONITOR),
+            Some(rect(10, 20, 30, 40))
+        );
+        // Fully off-screen
+        assert_eq!(rect(1920, 0, 100, 100).intersect(&MONITOR), None);
+    }
+
+    #[test]
+    fn follow_area_is_scaled_contain_fit_centered_on_cursor() {
+        // 1920x1080 monitor, 1.6 tablet → contain fit 1728x1080, half of it
+        // is 864x540
+        let cursor = Point::new(700.0, 400.0);
+        let area = follow_cursor(None, MONITOR, 1.6, 0.5, cursor).unwrap();
+        assert!((area.width() - 864.0).abs() < 0.01);
+        assert!((area.height() - 540.0).abs() < 0.01);
+        assert_eq!(area.center, cursor);
+    }
+
+    #[test]
+    fn follow_area_stays_while_cursor_inside() {
+        let area = follow_cursor(None, MONITOR, 1.6, 0.5, Point::new(700.0, 400.0)).unwrap();
+        // Anywhere inside the current area, including its edge
+        for cursor in [Point::new(300.0, 200.0), Point::new(1132.0, 670.0)] {
+            assert_eq!(
+                follow_cursor(Some(&area), MONITOR, 1.6, 0.5, cursor),
+                Some(area)
+            );
+        }
+    }
+
+    #[test]
+    fn follow_area_recenters_once_cursor_leaves() {
+        let area = follow_cursor(None, MONITOR, 1.6, 0.5, Point::new(700.0, 400.0)).unwrap();
+        let cursor = Point::new(1200.0, 500.0);
+        let moved = follow_cursor(Some(&area), MONITOR, 1.6, 0.5, cursor).unwrap();
+        assert_eq!(moved.center, cursor);
+        assert_eq!(moved.size, area.size);
+    }
+
+    #[test]
+    fn follow_area_stays_inside_container() {
+        // Cursor in the bottom-right corner of a monitor left of the primary
+        let monitor = rect(-1920, 0, 1920, 1080);
+        let area = follow_cursor(None, monitor, 1.6, 0.5, Point::new(-5.0, 1075.0)).unwrap();
+        assert_inside(&area, monitor);
+        assert!((area.right() - 0.0).abs() < 0.01);
+        assert!((area.bottom() - 1080.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn follow_area_resizes_with_container() {
+        // Moving to a smaller container shrinks the area even if the cursor
+        // is still inside the old one
+        let area = follow_cursor(None, MONITOR, 1.6, 0.5, Point::new(700.0, 400.0)).unwrap();
+        let window = rect(0, 0, 800, 500);
+        let moved = follow_cursor(Some(&area), window, 1.6, 0.5, Point::new(700.0, 400.0)).unwrap();
+        assert!((moved.width() - 400.0).abs() < 0.01);
+        assert_inside(&moved, window);
+    }
+
+    #[test]
+    fn desktop_space_round_trips() {
+        let space = DesktopSpace::from_monitors(&[MONITOR, rect(-1920, -200, 1920, 1080)]);
+        let area = Rect::new(Point::new(-700.0, 300.0), Size::new(864.0, 540.0));
+        assert_eq!(space.to_screen(&space.to_otd(&area)), area);
+    }
+
+    #[test]
+    fn primary_only_desktop_is_identity() {
+        let space = DesktopSpace::from_monitors(&[MONITOR]);
+        let area = fit_to_window(rect(100, 200, 800, 600), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        assert_eq!(space.to_otd(&area), area);
+    }
+
+    #[test]
+    fn monitor_left_of_primary_shifts_right() {
+        // [ secondary 1920x1080 at x=-1920 ][ primary 2560x1440 at 0,0 ]
+        let space =
+            DesktopSpace::from_monitors(&[rect(0, 0, 2560, 1440), rect(-1920, 0, 1920, 1080)]);
+        assert_eq!((space.origin_x, space.origin_y), (-1920, 0));
+
+        // Window on the secondary monitor has negative screen coordinates
+        let area = fit_to_window(rect(-1500, 100, 800, 600), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let otd = space.to_otd(&area);
+        assert!((otd.center.x - 820.0).abs() < 0.01); // -1100 + 1920
+        assert!((otd.center.y - 400.0).abs() < 0.01);
+        // Lands within the secondary monitor's span of OTD space (0..1920)
+        assert!(otd.center.x - otd.width() / 2.0 >= 0.0);
+        assert!(otd.center.x + otd.width() / 2.0 <= 1920.0);
+
+        // Window on the primary monitor lands past the secondary
+        let area = fit_to_window(rect(100, 100, 800, 600), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let otd = space.to_otd(&area);
+        assert!((otd.center.x - 2420.0).abs() < 0.01); // 500 + 1920
+        assert!(otd.center.x - otd.width() / 2.0 >= 1920.0);
+    }
+
+    #[test]
+    fn monitor_stacked_above_primary_shifts_down() {
+        // Secondary 1920x1080 directly above the primary
+        let space =
+            DesktopSpace::from_monitors(&[rect(0, 0, 1920, 1080), rect(0, -1080, 1920, 1080)]);
+        assert_eq!((space.origin_x, space.origin_y), (0, -1080));
+
+        let area = fit_to_window(rect(0, -1080, 1920, 1080), 16.0 / 9.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let otd = space.to_otd(&area);
+        assert!((otd.center.x - 960.0).abs() < 0.01);
+        assert!((otd.center.y - 540.0).abs() < 0.01); // top monitor is on top
+
+        let area = fit_to_window(rect(0, 0, 1920, 1080), 16.0 / 9.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let otd = space.to_otd(&area);
+        assert!((otd.center.y - 1620.0).abs() < 0.01); // 540 + 1080
+    }
+
+    #[test]
+    fn rotation_swaps_axes_on_quarter_turns() {
+        let full = tablet(160.0, 100.0, 0.0);
+        let expected = [
+            (0.0, 160.0, 100.0),
+            (90.0, 100.0, 160.0),
+            (180.0, 160.0, 100.0),
+            (270.0, 100.0, 160.0),
+        ];
+        for (rotation, width, height) in expected {
+            let rotated = rotate_tablet_area(&full, rotation);
+            assert!((rotated.rect.width() - width).abs() < 0.01, "{rotation}°");
+            assert!((rotated.rect.height() - height).abs() < 0.01, "{rotation}°");
+            assert!((rotated.rotation - rotation).abs() < 0.01);
+            assert!((rotated.rect.center.x - 80.0).abs() < 0.01);
+            assert!((rotated.rect.center.y - 50.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn rotation_is_relative_to_current_orientation() {
+        // Area already set up in portrait at 90° stays portrait at 270°
+        let portrait = tablet(100.0, 160.0, 90.0);
+        let rotated = rotate_tablet_area(&portrait, 270.0);
+        assert!((rotated.rect.width() - 100.0).abs() < 0.01);
+        assert!((rotated.rect.height() - 160.0).abs() < 0.01);
+
+        // ...and goes back to landscape at 0°
+        let rotated = rotate_tablet_area(&portrait, 0.0);
+        assert!((rotated.rect.width() - 160.0).abs() < 0.01);
+        assert!((rotated.rect.height() - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn rotated_aspect_ratio_fits_display_in_every_orientation() {
+        // 16:10 tablet mapped to a 1000x1000 window
+        let full = tablet(160.0, 100.0, 0.0);
+        let expected = [
+            (0.0, 1600.0, 1000.0),
+            (90.0, 1000.0, 1600.0),
+            (180.0, 1600.0, 1000.0),
+            (270.0, 1000.0, 1600.0),
+        ];
+        for (rotation, width, height) in expected {
+            let aspect = rotate_tablet_area(&full, rotation).rect.aspect_ratio();
+            let area = fit_to_window(rect(0, 0, 1000, 1000), aspect, FitMode::Cover, 0.0)
+                .unwrap()
+                .area;
+            assert!((area.width() - width).abs() < 0.01, "{rotation}°");
+            assert!((area.height() - height).abs() < 0.01, "{rotation}°");
+            assert!((area.width() / area.height() - aspect).abs() < 1e-9);
+        }
+    }
+
+    /// Width and height of the bounding box of `area` on the tablet.
+    fn footprint(area: &TabletArea) -> (f64, f64) {
+        let (sin, cos) = area.rotation.to_radians().sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+        let size = area.rect.size;
+        (
+            size.width * cos + size.height * sin,
+            size.width * sin + size.height * cos,
+        )
+    }
+
+    #[test]
+    fn slight_tilt_shrinks_area_to_stay_on_tablet() {
+        let full = tablet(160.0, 100.0, 0.0);
+        for rotation in [5.0, 12.5, 15.0, 20.0, 340.0, 352.5] {
+            let rotated = rotate_tablet_area(&full, rotation);
+            let (w, h) = footprint(&rotated);
+            assert!(w <= 160.0 + 1e-9 && h <= 100.0 + 1e-9, "{rotation}°");
+            // Stays landscape with the tablet's shape
+            assert!(
+                (rotated.rect.aspect_ratio() - 1.6).abs() < 1e-9,
+                "{rotation}°"
+            );
+            assert!(rotated.rect.width() < 160.0);
+            assert!((rotated.rotation - rotation).abs() < 1e-9);
+            assert!((rotated.rect.center.x - 80.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn tilt_touches_tablet_edges() {
+        // 15° on a 160x100 tablet is limited by the height:
+        // h * (1.6 * sin 15° + cos 15°) = 100
+        let rotated = rotate_tablet_area(&tablet(160.0, 100.0, 0.0), 15.0);
+        let expected_h = 100.0 / (1.6 * 15f64.to_radians().sin() + 15f64.to_radians().cos());
+        assert!((rotated.rect.height() - expected_h).abs() < 1e-6);
+        assert!((footprint(&rotated).1 - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn near_quarter_turn_flips_to_portrait() {
+        let rotated = rotate_tablet_area(&tablet(160.0, 100.0, 0.0), 80.0);
+        assert!(rotated.rect.height() > rotated.rect.width());
+        assert!((rotated.rect.aspect_ratio() - 1.0 / 1.6).abs() < 1e-9);
+        let (w, h) = footprint(&rotated);
+        assert!(w <= 160.0 + 1e-9 && h <= 100.0 + 1e-9);
+    }
+
+    #[test]
+    fn unchanged_rotation_keeps_area() {
+        let area = tablet(150.0, 95.0, 12.5);
+        assert_eq!(rotate_tablet_area(&area, 12.5), area);
+    }
+
+    #[test]
+    fn physical_area_is_one_to_one_at_unit_scale() {
+        // 152x95mm tablet on a 96 DPI monitor: 152 / 25.4 * 96 ≈ 574.5px
+        let density = PixelDensity::from_dpi(96.0, 96.0);
+        let area = physical_area(
+            rect(0, 0, 1920, 1080),
+            &tablet(152.0, 95.0, 0.0),
+            density,
+            1.0,
+        )
+        .unwrap();
+        assert!((area.width() - 574.49).abs() < 0.01);
+        assert!((area.height() - 359.06).abs() < 0.01);
+        assert!((area.center.x - 960.0).abs() < 0.01);
+        assert!((area.center.y - 540.0).abs() < 0.01);
+        // Tablet mm → screen mm is exactly 1:1
+        assert!((area.width() / density.x - 152.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn physical_area_ignores_window_size() {
+        let density = PixelDensity::from_dpi(110.0, 110.0);
+        let full = tablet(152.0, 95.0, 0.0);
+        let small = physical_area(rect(100, 100, 400, 300), &full, density, 1.0).unwrap();
+        let large = physical_area(rect(100, 100, 2400, 1300), &full, density, 1.0).unwrap();
+        assert!((small.width() - large.width()).abs() < 1e-9);
+        assert!((small.height() - large.height()).abs() < 1e-9);
+        assert!((small.center.x - 300.0).abs() < 0.01);
+        assert!((large.center.x - 1300.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn physical_area_rescales_with_pixel_density() {
+        // Moving from a 96 DPI to a 192 DPI monitor doubles the pixel size
+        let full = tablet(100.0, 50.0, 0.0);
+        let low = physical_area(
+            rect(0, 0, 800, 600),
+            &full,
+            PixelDensity::from_dpi(96.0, 96.0),
+            1.0,
+        )
+        .unwrap();
+        let high = physical_area(
+            rect(0, 0, 800, 600),
+            &full,
+            PixelDensity::from_dpi(192.0, 192.0),
+            1.0,
+        )
+        .unwrap();
+        assert!((high.width() - 2.0 * low.width()).abs() < 1e-9);
+        assert!((high.height() - 2.0 * low.height()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn physical_area_applies_scale() {
+        // 2 screen mm per tablet mm on a 254 DPI (10 px/mm) monitor
+        let density = PixelDensity::from_dpi(254.0, 254.0);
+        let area = physical_area(
+            rect(0, 0, 100, 100),
+            &tablet(100.0, 60.0, 0.0),
+            density,
+            2.0,
+        )
+        .unwrap();
+        assert!((area.width() - 2000.0).abs() < 1e-9);
+        assert!((area.height() - 1200.0).abs() < 1e-9);
+        assert!(
+            physical_area(rect(0, 0, 0, 100), &tablet(100.0, 60.0, 0.0), density, 1.0).is_none()
+        );
+    }
+
+    #[test]
+    fn fixed_area_keeps_size_and_follows_window_center() {
+        let size = Size::new(1200.0, 750.0);
+        for window in [rect(0, 0, 1920, 1080), rect(-1500, 300, 640, 480)] {
+            let area = fixed_area(window, size).unwrap();
+            assert_eq!(area.size, size);
+            assert_eq!(area.center, window.to_rect().center);
+        }
+        assert!(fixed_area(rect(0, 0, 0, 480), size).is_none());
+    }
+
+    #[test]
+    fn fixed_area_composes_with_anchor_and_clamp() {
+        // Anchored to the window's top-left corner, then pulled back onto the
+        // monitor
+        let window = rect(-200, 100, 800, 600);
+        let area = fixed_area(window, Size::new(1200.0, 750.0)).unwrap();
+        let anchored = anchor_to_window(&area, window, Anchor::TopLeft);
+        assert_eq!((anchored.left(), anchored.top()), (-200.0, 100.0));
+        let clamped = clamp_to_bounds(&anchored, MONITOR);
+        assert_eq!((clamped.left(), clamped.top()), (0.0, 100.0));
+    }
+
+    #[test]
+    fn parse_size() {
+        assert_eq!("1200x750".parse(), Ok(Size::<Px>::new(1200.0, 750.0)));
+        assert_eq!(" 800 X 500.5".parse(), Ok(Size::<Px>::new(800.0, 500.5)));
+        assert!("1200".parse::<Size<Px>>().is_err());
+        assert!("1200x0".parse::<Size<Px>>().is_err());
+        assert!("-5x10".parse::<Size<Px>>().is_err());
+    }
+
+    #[test]
+    fn parse_length_units() {
+        assert_eq!(parse_length("12.5mm", "mm"), Ok((12.5, false)));
+        assert_eq!(parse_length(" 40 ", "px"), Ok((40.0, false)));
+        assert_eq!(parse_length("5%", "px"), Ok((5.0, true)));
+        assert!(parse_length("40mm", "px").is_err());
+        assert!(parse_length("-1", "px").is_err());
+        assert!(parse_length("inf%", "px").is_err());
+    }
+
+    #[test]
+    fn parse_size_limit() {
+        assert_eq!(
+            "800x500".parse(),
+            Ok(SizeLimit::Pixels(Size::new(800.0, 500.0)))
+        );
+        assert_eq!("30%".parse(), Ok(SizeLimit::Percent(30.0)));
+        assert!("0%".parse::<SizeLimit>().is_err());
+        assert!("30".parse::<SizeLimit>().is_err());
+        assert!("abc%".parse::<SizeLimit>().is_err());
+        assert_eq!(
+            SizeLimit::Percent(25.0).resolve(MONITOR),
+            Size::new(480.0, 270.0)
+        );
+    }
+
+    #[test]
+    fn limit_size_grows_tiny_area_around_window() {
+        let window = rect(100, 100, 160, 100);
+        let area = fit_to_window(window, 1.6, FitMode::Contain, 0.0)
+            .unwrap()
+            .area;
+        let limited = limit_size(&area, window, Some(Size::new(800.0, 400.0)), None);
+        // Both axes reach the minimum and the tablet's shape is kept
+        assert_eq!(limited.size, Size::new(800.0, 500.0));
+        assert_eq!(limited.center, window.to_rect().center);
+    }
+
+    #[test]
+    fn limit_size_shrinks_large_area() {
+        let area = fit_to_window(MONITOR, 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let limited = limit_size(&area, MONITOR, None, Some(Size::new(960.0, 960.0)));
+        assert_eq!(limited.size, Size::new(960.0, 600.0));
+        assert_eq!(limited.center, MONITOR.to_rect().center);
+    }
+
+    #[test]
+    fn limit_size_keeps_area_within_limits() {
+        let window = rect(300, 200, 800, 500);
+        let area = anchor_to_window(
+            &fit_to_window(window, 1.6, FitMode::Contain, 0.0)
+                .unwrap()
+                .area,
+            window,
+            Anchor::TopLeft,
+        );
+        let limits = (
+            Some(Size::new(400.0, 250.0)),
+            Some(Size::new(1600.0, 1000.0)),
+        );
+        assert_eq!(limit_size(&area, window, limits.0, limits.1), area);
+    }
+
+    #[test]
+    fn limit_size_prefers_maximum_on_conflict() {
+        let window = rect(0, 0, 160, 100);
+        let area = fit_to_window(window, 1.6, FitMode::Contain, 0.0)
+            .unwrap()
+            .area;
+        let min = Some(Size::new(1600.0, 1000.0));
+        let max = Some(Size::new(320.0, 200.0));
+        assert_eq!(
+            limit_size(&area, window, min, max).size,
+            Size::new(320.0, 200.0)
+        );
+    }
+
+    #[test]
+    fn zoom_maps_tablet_to_fraction_of_window() {
+        let area = fit_to_window(rect(0, 0, 1600, 1000), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let zoom = Zoom {
+            factor: 0.25,
+            focus_x: 0.5,
+            focus_y: 0.5,
+        };
+        let zoomed = zoom_area(&area, rect(0, 0, 1600, 1000), &zoom);
+        assert!((zoomed.width() - 400.0).abs() < 0.01);
+        assert!((zoomed.height() - 250.0).abs() < 0.01);
+        assert!((zoomed.center.x - 800.0).abs() < 0.01);
+        assert!((zoomed.center.y - 500.0).abs() < 0.01);
+        // Aspect ratio is preserved
+        assert!((zoomed.width() / zoomed.height() - 1.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zoom_centers_on_focus_point_and_follows_window() {
+        let zoom = Zoom {
+            factor: 0.25,
+            focus_x: 0.25,
+            focus_y: 0.75,
+        };
+        let area = fit_to_window(rect(0, 0, 1600, 1000), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let zoomed = zoom_area(&area, rect(0, 0, 1600, 1000), &zoom);
+        assert!((zoomed.center.x - 400.0).abs() < 0.01);
+        assert!((zoomed.center.y - 750.0).abs() < 0.01);
+
+        // Same relative focus after the window moves
+        let area = fit_to_window(rect(-1600, 200, 1600, 1000), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let zoomed = zoom_area(&area, rect(-1600, 200, 1600, 1000), &zoom);
+        assert!((zoomed.center.x - -1200.0).abs() < 0.01);
+        assert!((zoomed.center.y - 950.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn zoom_near_edge_stays_inside_area() {
+        let area = fit_to_window(rect(0, 0, 1600, 1000), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let zoom = Zoom {
+            factor: 0.5,
+            focus_x: 0.0,
+            focus_y: 1.0,
+        };
+        let zoomed = zoom_area(&area, rect(0, 0, 1600, 1000), &zoom);
+        // 800x500 zoomed area pushed into the bottom-left corner
+        assert!((zoomed.center.x - 400.0).abs() < 0.01);
+        assert!((zoomed.center.y - 750.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn zoom_factor_one_is_identity() {
+        let area = fit_to_window(rect(100, 100, 1920, 1080), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let zoom = Zoom {
+            factor: 1.0,
+            focus_x: 0.1,
+            focus_y: 0.9,
+        };
+        assert_eq!(zoom_area(&area, rect(100, 100, 1920, 1080), &zoom), area);
+    }
+
+    /// Left, top, right and bottom edges of `area`.
+    fn edges(area: &DisplayArea) -> (f64, f64, f64, f64) {
+        (area.left(), area.top(), area.right(), area.bottom())
+    }
+
+    const ANCHORS: [Anchor; 9] = [
+        Anchor::TopLeft,
+        Anchor::Top,
+        Anchor::TopRight,
+        Anchor::Left,
+        Anchor::Center,
+        Anchor::Right,
+        Anchor::BottomLeft,
+        Anchor::Bottom,
+        Anchor::BottomRight,
+    ];
+
+    #[test]
+    fn anchor_matrix_for_cover() {
+        // Wide window 1920x1080 at (100, 50), tablet 4:3 → area 1920x1440,
+        // overflowing the window by 360px vertically
+        let window = rect(100, 50, 1920, 1080);
+        let area = fit_to_window(rect(100, 50, 1920, 1080), 4.0 / 3.0, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+
+        // (anchor, expected top edge); horizontally the area matches the
+        // window for every anchor
+        let expected = [
+            (Anchor::TopLeft, 50.0),
+            (Anchor::Top, 50.0),
+            (Anchor::TopRight, 50.0),
+            (Anchor::Left, -130.0),
+            (Anchor::Center, -130.0),
+            (Anchor::Right, -130.0),
+            (Anchor::BottomLeft, -310.0),
+            (Anchor::Bottom, -310.0),
+            (Anchor::BottomRight, -310.0),
+        ];
+        for (anchor, top) in expected {
+            let anchored = anchor_to_window(&area, window, anchor);
+            let (l, t, r, b) = edges(&anchored);
+            assert!((l - 100.0).abs() < 0.01, "{anchor:?}");
+            assert!((r - 2020.0).abs() < 0.01, "{anchor:?}");
+            assert!((t - top).abs() < 0.01, "{anchor:?}");
+            assert!((b - (top + 1440.0)).abs() < 0.01, "{anchor:?}");
+        }
+    }
+
+    #[test]
+    fn anchor_matrix_for_tall_window() {
+        // Tall window 800x1200 at (0, 0), tablet 1.6 → area 1920x1200,
+        // overflowing the window by 1120px horizontally
+        let window = rect(0, 0, 800, 1200);
+        let area = fit_to_window(rect(0, 0, 800, 1200), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+
+        let expected = [
+            (Anchor::TopLeft, 0.0),
+            (Anchor::Top, -560.0),
+            (Anchor::TopRight, -1120.0),
+            (Anchor::Left, 0.0),
+            (Anchor::Center, -560.0),
+            (Anchor::Right, -1120.0),
+            (Anchor::BottomLeft, 0.0),
+            (Anchor::Bottom, -560.0),
+            (Anchor::BottomRight, -1120.0),
+        ];
+        for (anchor, left) in expected {
+            let anchored = anchor_to_window(&area, window, anchor);
+            let (l, t, _, b) = edges(&anchored);
+            assert!((l - left).abs() < 0.01, "{anchor:?}");
+            assert!(t.abs() < 0.01, "{anchor:?}");
+            assert!((b - 1200.0).abs() < 0.01, "{anchor:?}");
+        }
+    }
+
+    #[test]
+    fn anchor_matrix_for_contain() {
+        // Letterboxed area 1440x1080 in a 1920x1080 window: the anchor
+        // decides which side the 480px strip is on
+        let window = rect(0, 0, 1920, 1080);
+        let area = fit_to_window(rect(0, 0, 1920, 1080), 4.0 / 3.0, FitMode::Contain, 0.0)
+            .unwrap()
+            .area;
+        for anchor in ANCHORS {
+            let anchored = anchor_to_window(&area, window, anchor);
+            let (l, t, r, b) = edges(&anchored);
+            let expected_left = match anchor {
+                Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => 0.0,
+                Anchor::Top | Anchor::Center | Anchor::Bottom => 240.0,
+                Anchor::TopRight | Anchor::Right | Anchor::BottomRight => 480.0,
+            };
+            assert!((l - expected_left).abs() < 0.01, "{anchor:?}");
+            assert!((r - (expected_left + 1440.0)).abs() < 0.01, "{anchor:?}");
+            assert!(t.abs() < 0.01 && (b - 1080.0).abs() < 0.01, "{anchor:?}");
+        }
+    }
+
+    #[test]
+    fn center_anchor_matches_fit_to_window() {
+        let area = fit_to_window(rect(100, 200, 800, 1200), 1.6, FitMode::Cover, 0.0)
+            .unwrap()
+            .area;
+        let anchored = anchor_to_window(&area, rect(100, 200, 800, 1200), Anchor::Center);
+        assert!((anchored.center.x - area.center.x).abs() < 1e-9);
+        assert!((anchored.center.y - area.center.y).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tolerance_maps_window_exactly_when_within_limit() {
+        // 1920x1080 (1.778) window, 1.6 tablet: 11.1% off, within 15%
+        let fit = fit_to_window(rect(0, 0, 1920, 1080), 1.6, FitMode::Cover, 0.15).unwrap();
+        assert!((fit.area.width() - 1920.0).abs() < 0.01);
+        assert!((fit.area.height() - 1080.0).abs() < 0.01);
+        assert!((fit.distortion - 0.1111).abs() < 0.001);
+    }
+
+    #[test]
+    fn tolerance_distorts_up_to_limit_before_overflowing() {
+        // 1920x1080 window, 4:3 tablet with 10% allowed: aspect becomes
+        // 4/3 * 1.1 = 1.4667, so height expands only to 1920 / 1.4667
+        let area = fit_to_window(rect(0, 0, 1920, 1080), 4.0 / 3.0, FitMode::Cover, 0.1)
+            .unwrap()
+            .area;
+        assert!((area.width() - 1920.0).abs() < 0.01);
+        assert!((area.height() - 1309.09).abs() < 0.01);
+        assert!((area.distortion(4.0 / 3.0) - 0.1).abs() < 1e-9);
+
+        // Tall window distorts the other way
+        let area = fit_to_window(rect(0, 0, 800, 1200), 1.6, FitMode::Cover, 0.1)
+            .unwrap()
+            .area;
+        assert!((area.height() - 1200.0).abs() < 0.01);
+        assert!((area.width() - 1745.45).abs() < 0.01); // 1200 * 1.6 / 1.1
+        assert!((area.distortion(1.6) - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tolerance_shrinks_letterbox() {
+        let strict = fit_to_window(rect(0, 0, 1920, 1080), 4.0 / 3.0, FitMode::Contain, 0.0)
+            .unwrap()
+            .area;
+        let loose = fit_to_window(rect(0, 0, 1920, 1080), 4.0 / 3.0, FitMode::Contain, 0.2)
+            .unwrap()
+            .area;
+        assert!((loose.height() - 1080.0).abs() < 0.01);
+        assert!((loose.width() - 1728.0).abs() < 0.01); // 1080 * 4/3 * 1.2
+        assert!(
+            letterbox(rect(0, 0, 1920, 1080), &loose).width
+                < letterbox(rect(0, 0, 1920, 1080), &strict).width
+        );
+    }
+
+    #[test]
+    fn zero_tolerance_is_undistorted() {
+        for mode in [FitMode::Cover, FitMode::Contain] {
+            let fit = fit_to_window(rect(0, 0, 2560, 1080), 1.6, mode, 0.0).unwrap();
+            assert!(fit.distortion.abs() < 1e-9);
+        }
+        // Stretch reports how far it bends the tablet; crop bends nothing
+        let fit = fit_to_window(rect(0, 0, 1600, 500), 1.6, FitMode::Stretch, 0.0).unwrap();
+        assert!((fit.distortion - 1.0).abs() < 1e-9);
+        let fit = fit_to_window(rect(0, 0, 1600, 500), 1.6, FitMode::Crop, 0.0).unwrap();
+        assert_eq!(fit.distortion, 0.0);
     }
 }
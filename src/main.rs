@@ -1,44 +1,256 @@
+mod canvas;
+mod cursor;
+mod eligibility;
 mod geometry;
+mod monitor;
 mod otd;
+mod pipeline;
+mod placement;
+mod preview;
+mod rules;
+mod target;
 mod window;
 
 use anyhow::{Context, Result};
+use canvas::CanvasSelector;
 use clap::Parser;
-use geometry::DisplayArea;
+use eligibility::Eligibility;
+use geometry::{
+    Anchor, Clamp, DesktopSpace, DisplayArea, FitMode, Follow, Insets, MappingMode, Px, Region,
+    Size, SizeLimit, TabletArea, TabletRegion, Zoom,
+};
+use pipeline::{CursorCell, Settings};
+use placement::{MonitorInfo, SpanPolicy, WorkAreaPolicy};
+use rules::{Rule, TargetRules};
 use std::cell::RefCell;
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicU32, Ordering};
+use target::{TargetSpec, parse_rotation};
 use windows::Win32::Foundation::*;
 use windows::Win32::System::Console::*;
 use windows::Win32::System::Threading::GetCurrentThreadId;
 use windows::Win32::UI::Accessibility::*;
 use windows::Win32::UI::HiDpi::*;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 #[derive(Parser)]
 #[command(name = "inkbound")]
 #[command(about = "Automatically maps your tablet to a target window")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    /// Process name or window title to track (case-insensitive substring match)
-    #[arg(short, long)]
-    target: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Window to track: a title or process name (case-insensitive substring
+    /// match), or rules like `process:exact=krita.exe && class:glob=Qt*`
+    /// matching title, process, path or class with contains, exact, glob or
+    /// regex, joined with && and ||. Append `;rotation=90`, `;fit=contain`,
+    /// `;insets=40` or `;canvas=largest` to override those options for this
+    /// target (write `\;` for a `;` in the rule). Can be given several times; earlier targets win when several
+    /// are open.
+    #[arg(short, long, required = true)]
+    target: Vec<TargetSpec>,
+
+    /// Never track windows matching this rule, even if they match --target
+    /// (same syntax as --target). Can be given several times.
+    #[arg(long)]
+    exclude: Vec<Rule>,
+
+    /// Also track owned windows, tool windows and dialogs. By default, when
+    /// a popup of the tracked app takes focus (a color picker, a file
+    /// dialog), the mapping stays on the window that owns it.
+    #[arg(long)]
+    track_popups: bool,
+
+    /// Smallest window that is tracked, as WIDTHxHEIGHT in pixels.
+    #[arg(long, default_value = "200x150")]
+    min_window_size: Size<Px>,
+
+    /// Map a child window of the target instead of its frame, for apps that
+    /// draw the canvas in one: largest (the largest child), id=N (the child
+    /// with control ID N), or a rule on the child's class and title like
+    /// `class=QOpenGLWidget` (the largest match). Falls back to the whole
+    /// window while no child matches.
+    #[arg(long)]
+    canvas: Option<CanvasSelector>,
 
     /// Override tablet name (auto-detected from OTD settings if not provided)
     #[arg(long)]
     tablet: Option<String>,
 
+    #[command(flatten)]
+    mapping: MappingArgs,
+
+    /// Fraction of the window's width and height the tablet covers while
+    /// precision zoom is on. Toggle zoom with Ctrl+Alt+Z; it centers on the
+    /// cursor.
+    #[arg(long, default_value_t = 0.25, value_parser = parse_fraction)]
+    zoom: f64,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Compute the mapping for a given window and monitor layout and write
+    /// it as text and SVG, without touching OTD or any window
+    Preview(preview::PreviewArgs),
+}
+
+/// Options that shape the mapping, shared by tracking and `preview`.
+#[derive(clap::Args)]
+struct MappingArgs {
     /// Tablet area rotation in degrees. If pen movements don't match your
     /// physical tablet orientation, try different values (0, 90, 180, 270).
-    #[arg(short, long, default_value_t = 0, value_parser = parse_rotation)]
-    rotation: u16,
+    /// Any angle is accepted, e.g. 15 for a slightly tilted tablet.
+    #[arg(short, long, default_value_t = 0.0, value_parser = parse_rotation, allow_hyphen_values = true)]
+    rotation: f64,
+
+    /// Only draw on part of the tablet area, as LEFT,TOP,WIDTH,HEIGHT from
+    /// its top-left corner, each in millimetres (20) or percent (10%).
+    #[arg(long)]
+    tablet_region: Option<TabletRegion>,
+
+    /// How the tablet's aspect ratio is fitted to the window: cover (map the
+    /// whole window, overflowing one axis), contain (letterbox inside the
+    /// window), stretch (match the window exactly, distorting strokes), or
+    /// crop (match the window exactly and crop the tablet area instead).
+    #[arg(long, default_value = "cover")]
+    fit: FitMode,
+
+    /// Let cover and contain stretch strokes by up to this many percent to
+    /// better fit the window before any overflow or letterbox is added.
+    #[arg(long, default_value_t = 0.0, value_parser = parse_percent)]
+    max_stretch: f64,
+
+    /// What the tablet is mapped to: window (fit to the window, see --fit),
+    /// physical (fixed physical scale centered on the window, see --scale),
+    /// cursor (fixed-size area following the cursor, see --follow), or fixed
+    /// (fixed pixel size centered on the window, see --size).
+    #[arg(long, default_value = "window")]
+    mode: MappingMode,
+
+    /// Screen millimetres per tablet millimetre with `--mode physical`.
+    #[arg(long, default_value_t = 1.0, value_parser = parse_scale)]
+    scale: f64,
+
+    /// Display area size in pixels with `--mode fixed`, as WIDTHxHEIGHT
+    /// (1200x750). Resizing the window only moves the area.
+    #[arg(long)]
+    size: Option<Size<Px>>,
+
+    /// Smallest display area, as WIDTHxHEIGHT in pixels (800x500) or a
+    /// percentage of the monitor (30%). Smaller areas are enlarged around
+    /// the window's center, keeping the tablet's shape.
+    #[arg(long)]
+    min_size: Option<SizeLimit>,
+
+    /// Largest display area, as WIDTHxHEIGHT in pixels or a percentage of
+    /// the monitor. Larger areas are shrunk around the window's center. Wins
+    /// over --min-size if the two conflict.
+    #[arg(long)]
+    max_size: Option<SizeLimit>,
+
+    /// What the area moves within with `--mode cursor`: monitor (the monitor
+    /// the cursor is on) or window (the target window).
+    #[arg(long, default_value = "monitor")]
+    follow: Follow,
+
+    /// Size of the area with `--mode cursor`, as a fraction of the largest
+    /// area with the tablet's shape that fits the monitor or window.
+    #[arg(long, default_value_t = 0.5, value_parser = parse_fraction)]
+    follow_scale: f64,
+
+    /// Which window edges the mapped area lines up with when it is larger or
+    /// smaller than the window (center, top, left, top-left, ...). With top,
+    /// the pen's top edge matches the window's and any overflow is below.
+    /// Only applies to `--mode window`; other areas stay centered.
+    #[arg(long, default_value = "center")]
+    anchor: Anchor,
+
+    /// Where the cropped tablet area sits on the tablet with `--fit crop`
+    /// (center, left, right, top, bottom, top-left, ...).
+    #[arg(long, default_value = "center")]
+    crop_anchor: Anchor,
+
+    /// Which part of a window spanning several monitors is mapped: whole,
+    /// largest (the monitor holding most of it), or cursor (the monitor the
+    /// cursor is on).
+    #[arg(long, default_value = "whole")]
+    span: SpanPolicy,
+
+    /// Map windows to the usable part of their monitor (without the taskbar)
+    /// instead of their own bounds: off, maximized, or snapped (maximized and
+    /// snapped windows).
+    #[arg(long, default_value = "off")]
+    work_area: WorkAreaPolicy,
+
+    /// Map only part of the window, as LEFT,TOP,WIDTH,HEIGHT fractions of its
+    /// size (0.2,0.1,0.6,0.8). Follows the window as it moves and resizes.
+    #[arg(long, default_value = "0,0,1,1")]
+    region: Region,
+
+    /// Trim toolbars and docks from the mapped window (or region): one value
+    /// for all edges or LEFT,TOP,RIGHT,BOTTOM, each in pixels (40) or percent
+    /// (5%).
+    #[arg(long, default_value = "0")]
+    insets: Insets,
+
+    /// Keep the mapped area on screen: none, monitor (the monitor holding
+    /// the window), or desktop (all monitors). Only the visible part of a
+    /// partly off-screen window is mapped.
+    #[arg(long, default_value = "none")]
+    clamp: Clamp,
+}
+
+impl MappingArgs {
+    fn settings(&self) -> Result<Settings> {
+        if self.mode == MappingMode::Fixed && self.size.is_none() {
+            anyhow::bail!("--mode fixed needs --size WIDTHxHEIGHT, e.g. --size 1200x750");
+        }
+
+        Ok(Settings {
+            mode: self.mode,
+            scale: self.scale,
+            size: self.size,
+            min_size: self.min_size,
+            max_size: self.max_size,
+            follow: self.follow,
+            follow_scale: self.follow_scale,
+            fit_mode: self.fit,
+            max_distortion: self.max_stretch,
+            anchor: self.anchor,
+            crop_anchor: self.crop_anchor,
+            span: self.span,
+            work_area: self.work_area,
+            region: self.region,
+            insets: self.insets,
+            clamp: self.clamp,
+        })
+    }
 }
 
-fn parse_rotation(s: &str) -> Result<u16, String> {
-    let v: u16 = s
+/// Parse a non-negative percentage (`10` or `10%`) into a fraction.
+fn parse_percent(s: &str) -> Result<f64, String> {
+    let (v, _) = geometry::parse_length(s, "%")?;
+    Ok(v / 100.0)
+}
+
+fn parse_scale(s: &str) -> Result<f64, String> {
+    let v: f64 = s
         .parse()
         .map_err(|_| format!("'{s}' is not a valid number"))?;
-    if !v.is_multiple_of(90) || v >= 360 {
-        return Err("rotation must be 0, 90, 180, or 270".to_string());
+    if !v.is_finite() || v <= 0.0 {
+        return Err("scale must be greater than 0".to_string());
+    }
+    Ok(v)
+}
+
+fn parse_fraction(s: &str) -> Result<f64, String> {
+    let v: f64 = s
+        .parse()
+        .map_err(|_| format!("'{s}' is not a valid number"))?;
+    if !(v > 0.0 && v <= 1.0) {
+        return Err("value must be greater than 0 and at most 1".to_string());
     }
     Ok(v)
 }
@@ -50,19 +262,57 @@ enum State {
     Suspended { hwnd: HWND },
 }
 
+/// The mapping settings for windows matching one `--target`.
+struct Target {
+    rotation: f64,
+    settings: Settings,
+    canvas: Option<CanvasSelector>,
+}
+
 struct AppState {
-    target: String,
+    /// Which windows to track. Include rule `i` selects `targets[i]`.
+    rules: TargetRules,
+    targets: Vec<Target>,
+    /// Index of the target whose settings are applied.
+    active: usize,
+    eligibility: Eligibility,
     state: State,
+    /// Child of the tracked window mapped instead of its frame, if the
+    /// active target has a canvas and it was found.
+    canvas: Option<HWND>,
+    /// Look the canvas up again with the next mapping: the tracked window's
+    /// children moved or appeared.
+    canvas_stale: bool,
+    /// No child matched when the canvas was last looked up, so the whole
+    /// window is mapped until the children change.
+    canvas_missing: bool,
     otd: otd::OtdBridge,
-    tablet_aspect_ratio: f64,
     in_move_size: bool,
     last_error_logged: std::time::Instant,
     last_applied_area: Option<DisplayArea>,
+    /// Where the cursor can move without changing the applied area, if it
+    /// follows the cursor.
+    cursor_cell: Option<CursorCell>,
+    /// The last mapping ignored `--region` and `--insets`, which was warned
+    /// about once.
+    trimmed_away: bool,
+    zoom_factor: f64,
+    /// Active precision zoom, toggled at runtime with the zoom hotkey.
+    zoom: Option<Zoom>,
+}
+
+impl AppState {
+    /// Mapping settings of the active target.
+    fn settings(&self) -> &Settings {
+        &self.targets[self.active].settings
+    }
 }
 
 const DEBOUNCE_TIMER_ID: usize = 1;
+const ZOOM_HOTKEY_ID: i32 = 1;
 const DEBOUNCE_MS: u32 = 100;
 const OBJID_WINDOW: i32 = 0;
+const OBJID_CURSOR: i32 = -9;
 const ERROR_LOG_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
 
 thread_local! {
@@ -74,6 +324,7 @@ thread_local! {
 struct RestoreInfo {
     tablet_name: String,
     original_display_area: DisplayArea,
+    original_tablet_area: TabletArea,
     daemon_pid: Option<u32>,
 }
 
@@ -83,13 +334,33 @@ static MAIN_THREAD_ID: AtomicU32 = AtomicU32::new(0);
 fn main() -> Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    let args = Args::parse();
+    if let Some(Command::Preview(preview)) = &args.command {
+        return preview::run(preview);
+    }
+    let settings = args.mapping.settings()?;
+    if args.target.is_empty() {
+        anyhow::bail!("--target is required");
+    }
+    let targets: Vec<Target> = args
+        .target
+        .iter()
+        .map(|spec| Target {
+            rotation: spec.rotation.unwrap_or(args.mapping.rotation),
+            settings: spec.settings(&settings),
+            canvas: spec.canvas.clone().or_else(|| args.canvas.clone()),
+        })
+        .collect();
+    let rules = TargetRules {
+        include: args.target.into_iter().map(|spec| spec.rule).collect(),
+        exclude: args.exclude,
+    };
+
     // Enable per-monitor DPI awareness for accurate window coordinates
     unsafe {
         let _ = SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
     }
 
-    let args = Args::parse();
-
     // Ensure OTD daemon is running (starts it if needed, stops on exit)
     let _daemon_guard = otd::ensure_daemon_running()?;
 
@@ -99,31 +370,60 @@ fn main() -> Result<()> {
         None => otd::detect_tablet_name().context("Failed to auto-detect tablet")?,
     };
 
-    // Create OTD bridge (saves original mapping and applies rotation)
-    let otd_bridge = otd::OtdBridge::new(tablet_name.clone(), args.rotation as f64)?;
-    let tablet_aspect_ratio = otd_bridge.tablet_aspect_ratio();
+    // Create OTD bridge (saves original mapping, applies tablet region and
+    // the first target's rotation)
+    let otd_bridge = otd::OtdBridge::new(
+        tablet_name.clone(),
+        targets[0].rotation,
+        args.mapping.tablet_region.as_ref(),
+    )?;
 
     // Store restore info globally for the Ctrl+C handler
     let daemon_pid = _daemon_guard.pid();
     RESTORE_INFO
         .set(RestoreInfo {
             tablet_name,
-            original_display_area: otd_bridge.original_display_area().clone(),
+            original_display_area: *otd_bridge.original_display_area(),
+            original_tablet_area: *otd_bridge.original_tablet_area(),
             daemon_pid,
         })
         .ok();
     MAIN_THREAD_ID.store(unsafe { GetCurrentThreadId() }, Ordering::SeqCst);
 
-    log::info!("Target: \"{}\"", args.target);
+    for (i, (rule, target)) in rules.include.iter().zip(&targets).enumerate() {
+        log::info!(
+            "Target #{}: \"{}\" (mode: {:?}, fit: {:?}, rotation: {}°)",
+            i + 1,
+            rule,
+            target.settings.mode,
+            target.settings.fit_mode,
+            target.rotation
+        );
+        if let Some(canvas) = &target.canvas {
+            log::info!("  canvas: {canvas}");
+        }
+    }
 
     let app_state = AppState {
-        target: args.target,
+        rules,
+        targets,
+        active: 0,
+        eligibility: Eligibility {
+            popups: args.track_popups,
+            min_size: args.min_window_size,
+        },
         state: State::WaitingForWindow,
+        canvas: None,
+        canvas_stale: false,
+        canvas_missing: false,
         otd: otd_bridge,
-        tablet_aspect_ratio,
         in_move_size: false,
         last_error_logged: std::time::Instant::now() - ERROR_LOG_INTERVAL,
         last_applied_area: None,
+        cursor_cell: None,
+        trimmed_away: false,
+        zoom_factor: args.zoom,
+        zoom: None,
     };
 
     APP.with(|app| {
@@ -139,14 +439,27 @@ fn main() -> Result<()> {
     // Install event hooks FIRST (before finding the window — avoids race condition)
     let hooks = install_event_hooks()?;
 
+    // Zoom hotkey is posted to this thread's message queue as WM_HOTKEY
+    let zoom_hotkey = unsafe {
+        RegisterHotKey(
+            None,
+            ZOOM_HOTKEY_ID,
+            MOD_CONTROL | MOD_ALT | MOD_NOREPEAT,
+            VK_Z.0 as u32,
+        )
+    };
+    if let Err(e) = &zoom_hotkey {
+        log::warn!("Failed to register zoom hotkey Ctrl+Alt+Z: {e}");
+    }
+
     // Now search for the target window
     let initial_hwnd = APP.with(|app| {
         let app = app.borrow();
         let app = app.as_ref().unwrap();
-        window::find_matching_window(&app.target)
+        window::find_matching_window(&app.rules, &app.eligibility)
     });
 
-    if let Some(hwnd) = initial_hwnd {
+    if let Some((hwnd, target)) = initial_hwnd {
         log::info!(
             "Found target window: \"{}\"",
             window::get_window_title(hwnd),
@@ -154,7 +467,7 @@ fn main() -> Result<()> {
         APP.with(|app| {
             let mut app = app.borrow_mut();
             if let Some(app) = app.as_mut() {
-                transition_to_tracking(app, hwnd);
+                transition_to_tracking(app, hwnd, target);
             }
         });
     } else {
@@ -168,7 +481,7 @@ fn main() -> Result<()> {
     // (if we started it, we're about to kill it — no point restoring)
     if daemon_pid.is_none() {
         APP.with(|app| {
-            if let Some(app) = app.borrow().as_ref()
+            if let Some(app) = app.borrow_mut().as_mut()
                 && let Err(e) = app.otd.restore_original()
             {
                 log::error!("Failed to restore original mapping: {e}");
@@ -182,6 +495,12 @@ fn main() -> Result<()> {
         }
     }
 
+    if zoom_hotkey.is_ok() {
+        unsafe {
+            let _ = UnregisterHotKey(None, ZOOM_HOTKEY_ID);
+        }
+    }
+
     log::info!("Exiting.");
     Ok(())
 }
@@ -195,6 +514,7 @@ fn install_event_hooks() -> Result<Vec<HWINEVENTHOOK>> {
         (EVENT_OBJECT_LOCATIONCHANGE, EVENT_OBJECT_LOCATIONCHANGE),
         (EVENT_OBJECT_SHOW, EVENT_OBJECT_HIDE),
         (EVENT_OBJECT_DESTROY, EVENT_OBJECT_DESTROY),
+        (EVENT_OBJECT_NAMECHANGE, EVENT_OBJECT_NAMECHANGE),
     ];
 
     for (min, max) in event_ranges {
@@ -227,8 +547,10 @@ unsafe extern "system" fn win_event_callback(
     _event_thread: u32,
     _event_time: u32,
 ) {
-    // Only process window-level events (not caret/cursor)
-    if event == EVENT_OBJECT_LOCATIONCHANGE && id_object != OBJID_WINDOW {
+    // Only process window-level events (including child windows, for
+    // canvases) and cursor moves, not carets, menus, titles of controls etc.
+    let cursor_move = event == EVENT_OBJECT_LOCATIONCHANGE && id_object == OBJID_CURSOR;
+    if !cursor_move && (id_object != OBJID_WINDOW || hwnd.is_invalid()) {
         return;
     }
 
@@ -253,6 +575,10 @@ unsafe extern "system" fn win_event_callback(
                 }
             }
 
+            EVENT_OBJECT_LOCATIONCHANGE if id_object == OBJID_CURSOR => {
+                handle_cursor_move(app);
+            }
+
             EVENT_OBJECT_LOCATIONCHANGE => {
                 handle_location_change(app, hwnd);
             }
@@ -273,6 +599,10 @@ unsafe extern "system" fn win_event_callback(
                 handle_destroy(app, hwnd);
             }
 
+            EVENT_OBJECT_NAMECHANGE => {
+                handle_name_change(app, hwnd);
+            }
+
             _ => {}
         }
     });
@@ -295,21 +625,44 @@ fn handle_location_change(app: &mut AppState, hwnd: HWND) {
                 }
             }
         }
+        // Docks resized or the canvas replaced (switching documents)
+        State::Tracking { hwnd: tracked } if is_canvas_candidate(app, tracked, hwnd) => {
+            mark_canvas_stale(app);
+        }
         State::Suspended { hwnd: tracked } if hwnd == tracked => {
             if !window::is_minimized(hwnd) {
                 log::info!(
                     "Target window restored: \"{}\"",
                     window::get_window_title(hwnd)
                 );
-                transition_to_tracking(app, hwnd);
+                transition_to_tracking(app, hwnd, app.active);
             }
         }
         _ => {}
     }
 }
 
+fn handle_cursor_move(app: &mut AppState) {
+    // Only mappings that depend on the cursor need updating, and only once
+    // it leaves the part of the screen the current area was computed for
+    if let State::Tracking { hwnd } = app.state
+        && app.settings().follows_cursor()
+        && !app.in_move_size
+        && !app
+            .cursor_cell
+            .zip(cursor::position())
+            .is_some_and(|(cell, cursor)| cell.contains(cursor))
+    {
+        update_mapping(app, hwnd);
+    }
+}
+
 fn handle_foreground(app: &mut AppState, hwnd: HWND) {
-    if window::matches_target(hwnd, &app.target) && window::is_valid_window(hwnd) {
+    // A popup of the tracked app hands focus back to its owner, which is
+    // already tracked, so the mapping stays put
+    if let Some((hwnd, target)) = window::trackable_match(hwnd, &app.rules, &app.eligibility)
+        && window::is_valid_window(hwnd)
+    {
         // Only log and update if we're switching to a different window
         let already_tracking =
             matches!(app.state, State::Tracking { hwnd: tracked } if tracked == hwnd);
@@ -318,7 +671,7 @@ fn handle_foreground(app: &mut AppState, hwnd: HWND) {
                 "Target window focused: \"{}\"",
                 window::get_window_title(hwnd)
             );
-            transition_to_tracking(app, hwnd);
+            transition_to_tracking(app, hwnd, target);
         }
     }
 }
@@ -326,12 +679,15 @@ fn handle_foreground(app: &mut AppState, hwnd: HWND) {
 fn handle_show(app: &mut AppState, hwnd: HWND) {
     match app.state {
         State::WaitingForWindow => {
-            if window::matches_target(hwnd, &app.target) && window::is_valid_window(hwnd) {
+            if let Some((hwnd, target)) =
+                window::trackable_match(hwnd, &app.rules, &app.eligibility)
+                && window::is_valid_window(hwnd)
+            {
                 log::info!(
                     "Target window appeared: \"{}\"",
                     window::get_window_title(hwnd)
                 );
-                transition_to_tracking(app, hwnd);
+                transition_to_tracking(app, hwnd, target);
             }
         }
         State::Suspended { hwnd: tracked } if hwnd == tracked => {
@@ -340,9 +696,13 @@ fn handle_show(app: &mut AppState, hwnd: HWND) {
                     "Target window restored: \"{}\"",
                     window::get_window_title(hwnd)
                 );
-                transition_to_tracking(app, hwnd);
+                transition_to_tracking(app, hwnd, app.active);
             }
         }
+        // A canvas created later, e.g. when a document is opened
+        State::Tracking { hwnd: tracked } if is_canvas_candidate(app, tracked, hwnd) => {
+            mark_canvas_stale(app);
+        }
         _ => {}
     }
 }
@@ -354,6 +714,8 @@ fn handle_hide(app: &mut AppState, hwnd: HWND) {
         log::info!("Target window hidden");
         app.state = State::Suspended { hwnd };
         restore_original_quietly(app);
+    } else {
+        handle_canvas_gone(app, hwnd);
     }
 }
 
@@ -368,6 +730,41 @@ fn handle_destroy(app: &mut AppState, hwnd: HWND) {
         log::info!("Target window closed — waiting for it to reappear...");
         app.state = State::WaitingForWindow;
         restore_original_quietly(app);
+    } else {
+        handle_canvas_gone(app, hwnd);
+    }
+}
+
+/// Move the mapping off a canvas that was hidden or destroyed. Its rect is
+/// stale by then, and a destroyed child is no longer a descendant, so this
+/// can't wait for a location change.
+fn handle_canvas_gone(app: &mut AppState, hwnd: HWND) {
+    if let State::Tracking { hwnd: tracked } = app.state
+        && app.canvas == Some(hwnd)
+    {
+        app.canvas_stale = true;
+        update_mapping(app, tracked);
+    }
+}
+
+fn handle_name_change(app: &mut AppState, hwnd: HWND) {
+    match app.state {
+        // Switching to a browser tab or document that is excluded
+        State::Tracking { hwnd: tracked }
+            if hwnd == tracked && window::is_excluded(hwnd, &app.rules) =>
+        {
+            log::info!(
+                "Target window excluded after its title changed: \"{}\" — waiting for another match...",
+                window::get_window_title(hwnd)
+            );
+            app.state = State::WaitingForWindow;
+            restore_original_quietly(app);
+        }
+        // Switching back from an excluded tab or document
+        State::WaitingForWindow if hwnd == unsafe { GetForegroundWindow() } => {
+            handle_foreground(app, hwnd);
+        }
+        _ => {}
     }
 }
 
@@ -377,46 +774,245 @@ fn is_tracked(app: &AppState, hwnd: HWND) -> bool {
     matches!(app.state, State::Tracking { hwnd: tracked } if tracked == hwnd)
 }
 
-fn transition_to_tracking(app: &mut AppState, hwnd: HWND) {
+/// Whether `hwnd` is a child of the tracked window that could be its canvas.
+fn is_canvas_candidate(app: &AppState, tracked: HWND, hwnd: HWND) -> bool {
+    app.targets[app.active].canvas.is_some() && window::is_descendant(tracked, hwnd)
+}
+
+/// Look the canvas up again with the next mapping, which is debounced like
+/// programmatic moves.
+fn mark_canvas_stale(app: &mut AppState) {
+    app.canvas_stale = true;
+    if !app.in_move_size {
+        let timer_fn: TIMERPROC = Some(debounce_timer_callback);
+        unsafe {
+            SetTimer(None, DEBOUNCE_TIMER_ID, DEBOUNCE_MS, Some(timer_fn));
+        }
+    }
+}
+
+/// Start tracking `hwnd` with the settings of `targets[target]`.
+fn transition_to_tracking(app: &mut AppState, hwnd: HWND, target: usize) {
+    // Titles change while a window is hidden or minimized (switching
+    // documents or tabs), so exclusions are checked again on every restore
+    if window::is_excluded(hwnd, &app.rules) {
+        log::info!(
+            "Window excluded: \"{}\" — waiting for another match...",
+            window::get_window_title(hwnd)
+        );
+        app.state = State::WaitingForWindow;
+        restore_original_quietly(app);
+        return;
+    }
+
+    // Zoom is relative to the window it was turned on in
+    let same_window = matches!(app.state, State::Tracking { hwnd: tracked } if tracked == hwnd);
+    if !same_window || target != app.active {
+        reset_zoom(app);
+    }
+
+    let switched = target != app.active;
+    if switched {
+        log::info!(
+            "Switching to target #{}: \"{}\"",
+            target + 1,
+            app.rules.include[target]
+        );
+        app.active = target;
+        app.otd.set_rotation(app.targets[target].rotation);
+        // The new target's tablet area must be sent even if the display
+        // area happens to be the same
+        app.last_applied_area = None;
+    }
+
     app.state = State::Tracking { hwnd };
+    app.trimmed_away = false;
+    app.canvas = None;
+    app.canvas_missing = false;
     app.in_move_size = false;
     update_mapping(app, hwnd);
+
+    // Without a mapping to carry it, the new target's rotation is sent on its
+    // own, or the pen would keep the previous target's orientation
+    if switched
+        && app.last_applied_area.is_none()
+        && let Err(e) = app.otd.apply_rotation()
+    {
+        log::warn!("Failed to rotate the tablet area: {e}");
+    }
 }
 
-fn update_mapping(app: &mut AppState, hwnd: HWND) {
-    let Some((left, top, width, height)) = window::get_window_rect(hwnd) else {
+/// Look up the tracked window's canvas if the active target has one and the
+/// child found last is gone, hidden or may no longer be the one to pick.
+/// A miss is remembered until the children change, as the lookup walks
+/// every child window.
+fn refresh_canvas(app: &mut AppState, hwnd: HWND) {
+    let Some(selector) = &app.targets[app.active].canvas else {
+        app.canvas = None;
         return;
     };
+    let current_valid = app.canvas.is_some_and(|canvas| {
+        window::is_descendant(hwnd, canvas) && window::is_valid_window(canvas)
+    });
+    if !app.canvas_stale && (current_valid || app.canvas_missing) {
+        return;
+    }
 
-    let Some(area) = geometry::fit_to_window(left, top, width, height, app.tablet_aspect_ratio)
-    else {
+    app.canvas_stale = false;
+    let found = window::find_canvas(hwnd, selector);
+    match found {
+        Some(canvas) if found != app.canvas => log::info!(
+            "Mapping canvas \"{}\" ({selector})",
+            window::get_window_class(canvas)
+        ),
+        None if !app.canvas_missing => {
+            log::info!("No canvas matching {selector} — mapping the whole window")
+        }
+        _ => {}
+    }
+    app.canvas_missing = found.is_none();
+    app.canvas = found;
+}
+
+fn update_mapping(app: &mut AppState, hwnd: HWND) {
+    app.cursor_cell = None;
+    refresh_canvas(app, hwnd);
+    // Enumerated once per update, as cursor moves can trigger many
+    let monitors = monitor::monitors();
+    let space = monitor::desktop_space(&monitors);
+    let Some(mapping) = compute_area(app, hwnd, &monitors, space) else {
         return;
     };
+    if mapping.trimmed_away && !app.trimmed_away {
+        log::warn!(
+            "Window is too small for --region and --insets — mapping the whole window: \"{}\"",
+            window::get_window_title(hwnd)
+        );
+    }
+    app.trimmed_away = mapping.trimmed_away;
+
+    // OTD measures the display area from the virtual desktop's top-left
+    // corner, not the primary monitor's
+    let area = space.to_otd(&mapping.area);
 
     // Skip if the area hasn't changed (avoids spamming OTD)
     if app.last_applied_area.as_ref() == Some(&area) {
+        app.cursor_cell = mapping.cursor_cell;
         return;
     }
 
+    if let Some(size_limit) = mapping.size_limit {
+        log::info!("{size_limit}");
+    }
+
     log::debug!(
         "Mapping tablet to [{:.0}x{:.0}@<{:.0}, {:.0}>]",
-        area.width,
-        area.height,
-        area.center_x,
-        area.center_y
+        area.width(),
+        area.height(),
+        area.center.x,
+        area.center.y
     );
+    if mapping.distortion > 1e-6 {
+        log::debug!(
+            "Aspect ratio stretched by {:.1}%",
+            mapping.distortion * 100.0
+        );
+    }
 
-    if let Err(e) = app.otd.set_display_area(&area)
+    if let Err(e) = app.otd.apply_mapping(&area, app.settings().crop())
         && app.last_error_logged.elapsed() >= ERROR_LOG_INTERVAL
     {
         log::warn!("Failed to update display area: {e}");
         app.last_error_logged = std::time::Instant::now();
     } else {
         app.last_applied_area = Some(area);
+        app.cursor_cell = mapping.cursor_cell;
+    }
+}
+
+/// Describe the tracked window (or its canvas) and the desktop around it.
+fn scene<'a>(
+    app: &AppState,
+    hwnd: HWND,
+    monitors: &'a [MonitorInfo],
+) -> Option<pipeline::Scene<'a>> {
+    Some(pipeline::Scene {
+        window: window::get_window_rect(app.canvas.unwrap_or(hwnd))?,
+        maximized: window::is_maximized(hwnd),
+        monitors,
+        cursor: app
+            .settings()
+            .follows_cursor()
+            .then(cursor::position)
+            .flatten(),
+    })
+}
+
+/// Compute the display area for the tracked window in screen coordinates.
+fn compute_area(
+    app: &AppState,
+    hwnd: HWND,
+    monitors: &[MonitorInfo],
+    space: DesktopSpace,
+) -> Option<pipeline::Mapping> {
+    let settings = app.settings();
+    let scene = scene(app, hwnd, monitors)?;
+
+    // The area currently mapped, back in screen coordinates
+    let previous = app.last_applied_area.map(|area| space.to_screen(&area));
+
+    pipeline::compute_area(
+        settings,
+        app.otd.tablet_area(),
+        &scene,
+        app.zoom.as_ref(),
+        previous.as_ref(),
+    )
+}
+
+/// Turn precision zoom on (centered on the cursor) or off.
+fn toggle_zoom(app: &mut AppState) {
+    let State::Tracking { hwnd } = app.state else {
+        return;
+    };
+
+    if app.zoom.take().is_some() {
+        log::info!("Zoom off");
+    } else if app.settings().mode == MappingMode::Cursor {
+        // The area already follows the cursor at its own scale
+        log::info!("Zoom has no effect with --mode cursor");
+        return;
+    } else {
+        let monitors = monitor::monitors();
+        let Some(scene) = scene(app, hwnd, &monitors) else {
+            return;
+        };
+        // Focus is relative to the rect the pipeline zooms within, which
+        // --span and --work-area may make differ from the window
+        let frame = pipeline::zoom_frame(app.settings(), &scene);
+        let cursor = cursor::position().unwrap_or(frame.to_rect().center);
+        let zoom = Zoom::focused_on(app.zoom_factor, frame, cursor);
+        log::info!(
+            "Zoom on: {:.0}% at ({:.0}%, {:.0}%) of the window",
+            zoom.factor * 100.0,
+            zoom.focus_x * 100.0,
+            zoom.focus_y * 100.0
+        );
+        app.zoom = Some(zoom);
+    }
+
+    update_mapping(app, hwnd);
+}
+
+/// Turn precision zoom off, if it is on.
+fn reset_zoom(app: &mut AppState) {
+    if app.zoom.take().is_some() {
+        log::info!("Zoom off");
     }
 }
 
 fn restore_original_quietly(app: &mut AppState) {
+    reset_zoom(app);
     app.last_applied_area = None;
     if let Err(e) = app.otd.restore_original() {
         log::warn!("Failed to restore original mapping: {e}");
@@ -448,16 +1044,8 @@ unsafe extern "system" fn ctrl_handler(ctrl_type: u32) -> BOOL {
                     .output();
             } else {
                 // Daemon was already running — restore original mapping
-                let _ = std::process::Command::new("OpenTabletDriver.Console.exe")
-                    .args([
-                        "setdisplayarea",
-                        &info.tablet_name,
-                        &info.original_display_area.width.to_string(),
-                        &info.original_display_area.height.to_string(),
-                        &info.original_display_area.center_x.to_string(),
-                        &info.original_display_area.center_y.to_string(),
-                    ])
-                    .output();
+                let _ = otd::set_display_area(&info.tablet_name, &info.original_display_area);
+                let _ = otd::set_tablet_area(&info.tablet_name, &info.original_tablet_area);
             }
         }
 
@@ -478,6 +1066,14 @@ fn run_message_loop() {
     unsafe {
         let mut msg = MSG::default();
         while GetMessageW(&mut msg, None, 0, 0).into() {
+            if msg.message == WM_HOTKEY && msg.wParam.0 == ZOOM_HOTKEY_ID as usize {
+                APP.with(|app| {
+                    if let Some(app) = app.borrow_mut().as_mut() {
+                        toggle_zoom(app);
+                    }
+                });
+                continue;
+            }
             let _ = TranslateMessage(&msg);
             DispatchMessageW(&msg);
         }
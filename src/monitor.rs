@@ -0,0 +1,67 @@
+use crate::geometry::{DesktopSpace, IntRect, PixelDensity};
+use crate::placement::MonitorInfo;
+use windows::Win32::Foundation::*;
+use windows::Win32::Graphics::Gdi::*;
+use windows::Win32::UI::HiDpi::*;
+
+/// Get the bounds, work area and pixel density of every monitor.
+pub fn monitors() -> Vec<MonitorInfo> {
+    let mut handles: Vec<HMONITOR> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            None,
+            None,
+            Some(collect_monitors_callback),
+            LPARAM(&raw mut handles as isize),
+        );
+    }
+    handles.into_iter().filter_map(monitor_info).collect()
+}
+
+fn monitor_info(monitor: HMONITOR) -> Option<MonitorInfo> {
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !unsafe { GetMonitorInfoW(monitor, &mut info) }.as_bool() {
+        return None;
+    }
+
+    Some(MonitorInfo {
+        bounds: info.rcMonitor.into(),
+        work_area: info.rcWork.into(),
+        density: pixel_density(monitor),
+    })
+}
+
+/// Get the physical pixel density of a monitor, from the raw DPI it reports
+/// via EDID. Returns `None` if the monitor doesn't report a physical size.
+fn pixel_density(monitor: HMONITOR) -> Option<PixelDensity> {
+    let (mut dpi_x, mut dpi_y) = (0u32, 0u32);
+    unsafe { GetDpiForMonitor(monitor, MDT_RAW_DPI, &mut dpi_x, &mut dpi_y) }.ok()?;
+    if dpi_x == 0 || dpi_y == 0 {
+        return None;
+    }
+
+    Some(PixelDensity::from_dpi(dpi_x as f64, dpi_y as f64))
+}
+
+/// Get the mapping from screen coordinates to OTD display coordinates for
+/// the monitor layout returned by [`monitors`].
+pub fn desktop_space(monitors: &[MonitorInfo]) -> DesktopSpace {
+    let bounds: Vec<IntRect> = monitors.iter().map(|m| m.bounds).collect();
+    DesktopSpace::from_monitors(&bounds)
+}
+
+unsafe extern "system" fn collect_monitors_callback(
+    monitor: HMONITOR,
+    _hdc: HDC,
+    _rect: *mut RECT,
+    lparam: LPARAM,
+) -> BOOL {
+    unsafe {
+        let handles = &mut *(lparam.0 as *mut Vec<HMONITOR>);
+        handles.push(monitor);
+    }
+    BOOL(1)
+}
@@ -1,4 +1,4 @@
-use crate::geometry::{DisplayArea, TabletArea};
+use crate::geometry::{self, Anchor, DisplayArea, Point, Rect, Size, TabletArea, TabletRegion};
 use anyhow::{Context, Result, bail};
 use std::process::{Child, Command};
 
@@ -66,109 +66,206 @@ pub struct OtdBridge {
     tablet_name: String,
     original_display_area: DisplayArea,
     original_tablet_area: TabletArea,
-    tablet_aspect_ratio: f64,
+    /// Tablet area restricted to the requested region (if any), before
+    /// rotation.
+    active_area: TabletArea,
+    /// Active tablet area with the requested rotation. Cropped tablet areas
+    /// are derived from this.
+    tablet_area: TabletArea,
+    /// Tablet area currently applied in OTD, so it is only re-sent on change.
+    applied_tablet_area: TabletArea,
 }
 
 impl OtdBridge {
     /// Create a new bridge, saving original state. `rotation_degrees` is the
-    /// desired tablet rotation (from the --orientation flag).
-    pub fn new(tablet_name: String, rotation_degrees: f64) -> Result<Self> {
+    /// desired tablet rotation (from the --rotation flag) and `region` the
+    /// part of the tablet area to draw on (from the --tablet-region flag).
+    pub fn new(
+        tablet_name: String,
+        rotation_degrees: f64,
+        region: Option<&TabletRegion>,
+    ) -> Result<Self> {
         let (display_area, tablet_area) = get_areas(&tablet_name)?;
 
-        // Always use the tablet's native aspect ratio (width/height as OTD
-        // reports). OTD's rotation parameter handles the axis swap internally —
-        // the display area is always in screen coordinates.
-        let tablet_aspect_ratio = tablet_area.width / tablet_area.height;
+        let active_area = match region {
+            Some(region) => {
+                geometry::restrict_tablet_area(&tablet_area, region).with_context(|| {
+                    format!(
+                        "Tablet region doesn't fit the {:.1}x{:.1}mm tablet area",
+                        tablet_area.rect.width(),
+                        tablet_area.rect.height()
+                    )
+                })?
+            }
+            None => tablet_area,
+        };
+
+        // The display area is always in screen coordinates and OTD maps the
+        // tablet area's width onto it, so the aspect ratio to match is that
+        // of the area once rotated. Quarter turns swap its axes; other angles
+        // shrink it to keep its corners on the tablet.
+        let rotated_area = geometry::rotate_tablet_area(&active_area, rotation_degrees);
+        let tablet_aspect_ratio = rotated_area.rect.aspect_ratio();
 
         log::info!("Tablet: {tablet_name}");
         log::info!("Original display area: {display_area:?}");
         log::info!(
-            "Tablet area: {:.1}x{:.1}, rotation: {:.0}°",
-            tablet_area.width,
-            tablet_area.height,
+            "Tablet area: {:.1}x{:.1}, rotation: {}°",
+            tablet_area.rect.width(),
+            tablet_area.rect.height(),
             tablet_area.rotation
         );
+        if region.is_some() {
+            log::info!(
+                "Active tablet region: {:.1}x{:.1} at <{:.1}, {:.1}>",
+                active_area.rect.width(),
+                active_area.rect.height(),
+                active_area.rect.center.x,
+                active_area.rect.center.y
+            );
+        }
         log::info!("Tablet aspect ratio: {tablet_aspect_ratio:.3}");
 
-        let bridge = Self {
+        let mut bridge = Self {
             tablet_name,
             original_display_area: display_area,
             original_tablet_area: tablet_area,
-            tablet_aspect_ratio,
+            active_area,
+            tablet_area: rotated_area,
+            applied_tablet_area: tablet_area,
         };
 
-        // Apply the requested rotation
-        if rotation_degrees != bridge.original_tablet_area.rotation {
-            log::info!("Setting tablet rotation to {rotation_degrees:.0}°");
-            bridge.set_tablet_rotation(rotation_degrees)?;
+        // Apply the requested region and rotation
+        if rotated_area != bridge.original_tablet_area {
+            log::info!(
+                "Setting tablet area to {:.1}x{:.1}, rotation {rotation_degrees}°",
+                rotated_area.rect.width(),
+                rotated_area.rect.height()
+            );
+            bridge.apply_tablet_area(&rotated_area)?;
         }
 
         Ok(bridge)
     }
 
-    pub fn tablet_aspect_ratio(&self) -> f64 {
-        self.tablet_aspect_ratio
-    }
-
     pub fn original_display_area(&self) -> &DisplayArea {
         &self.original_display_area
     }
 
-    pub fn set_display_area(&self, area: &DisplayArea) -> Result<()> {
-        let output = Command::new("OpenTabletDriver.Console.exe")
-            .args([
-                "setdisplayarea",
-                &self.tablet_name,
-                &area.width.to_string(),
-                &area.height.to_string(),
-                &area.center_x.to_string(),
-                &area.center_y.to_string(),
-            ])
-            .output()
-            .context("Failed to run OpenTabletDriver.Console.exe — is the daemon running?")?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            bail!("setdisplayarea failed: {}", stderr.trim());
-        }
+    /// Full tablet area with the requested rotation, in millimetres.
+    pub fn tablet_area(&self) -> &TabletArea {
+        &self.tablet_area
+    }
 
-        Ok(())
+    pub fn original_tablet_area(&self) -> &TabletArea {
+        &self.original_tablet_area
     }
 
-    fn set_tablet_rotation(&self, rotation: f64) -> Result<()> {
-        let ta = &self.original_tablet_area;
-        let output = Command::new("OpenTabletDriver.Console.exe")
-            .args([
-                "settabletarea",
-                &self.tablet_name,
-                &ta.width.to_string(),
-                &ta.height.to_string(),
-                &ta.center_x.to_string(),
-                &ta.center_y.to_string(),
-                &rotation.to_string(),
-            ])
-            .output()
-            .context("Failed to set tablet rotation")?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            bail!("settabletarea failed: {}", stderr.trim());
-        }
+    /// Change the tablet rotation, e.g. when switching to a target with its
+    /// own rotation. The rotated area is sent to OTD with the next mapping,
+    /// or by [`Self::apply_rotation`].
+    pub fn set_rotation(&mut self, rotation_degrees: f64) {
+        self.tablet_area = geometry::rotate_tablet_area(&self.active_area, rotation_degrees);
+        log::info!(
+            "Tablet rotation: {rotation_degrees}°, aspect ratio: {:.3}",
+            self.tablet_area.rect.aspect_ratio()
+        );
+    }
+
+    /// Map the tablet to `area`. With `crop` set, the tablet area is cropped
+    /// to the display area's aspect ratio (anchored as given) and applied
+    /// along with it; otherwise the full tablet area is used.
+    pub fn apply_mapping(&mut self, area: &DisplayArea, crop: Option<Anchor>) -> Result<()> {
+        area.validate().map_err(anyhow::Error::msg)?;
+        let tablet_area = match crop {
+            Some(anchor) => {
+                geometry::crop_tablet_area(&self.tablet_area, area.aspect_ratio(), anchor)
+            }
+            None => self.tablet_area,
+        };
+        self.apply_tablet_area(&tablet_area)?;
+        set_display_area(&self.tablet_name, area)
+    }
+
+    /// Send the rotated tablet area to OTD on its own, for when no mapping
+    /// is applied with it.
+    pub fn apply_rotation(&mut self) -> Result<()> {
+        let area = self.tablet_area;
+        self.apply_tablet_area(&area)
+    }
 
+    fn apply_tablet_area(&mut self, area: &TabletArea) -> Result<()> {
+        if *area == self.applied_tablet_area {
+            return Ok(());
+        }
+        log::debug!(
+            "Setting tablet area to [{:.1}x{:.1}@<{:.1}, {:.1}>:{}°]",
+            area.rect.width(),
+            area.rect.height(),
+            area.rect.center.x,
+            area.rect.center.y,
+            area.rotation
+        );
+        set_tablet_area(&self.tablet_name, area)?;
+        self.applied_tablet_area = *area;
         Ok(())
     }
 
-    pub fn restore_original(&self) -> Result<()> {
+    pub fn restore_original(&mut self) -> Result<()> {
         log::info!("Restoring original settings");
-        self.set_display_area(&self.original_display_area)?;
-        if self.original_tablet_area.rotation != 0.0 {
-            // Only restore rotation if we changed it
-        }
-        self.set_tablet_rotation(self.original_tablet_area.rotation)?;
+        set_display_area(&self.tablet_name, &self.original_display_area)?;
+        set_tablet_area(&self.tablet_name, &self.original_tablet_area)?;
+        self.applied_tablet_area = self.original_tablet_area;
         Ok(())
     }
 }
 
+/// Set the display area for `tablet_name` via `setdisplayarea`.
+pub fn set_display_area(tablet_name: &str, area: &DisplayArea) -> Result<()> {
+    let output = Command::new("OpenTabletDriver.Console.exe")
+        .args([
+            "setdisplayarea",
+            tablet_name,
+            &area.width().to_string(),
+            &area.height().to_string(),
+            &area.center.x.to_string(),
+            &area.center.y.to_string(),
+        ])
+        .output()
+        .context("Failed to run OpenTabletDriver.Console.exe — is the daemon running?")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("setdisplayarea failed: {}", stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Set the tablet area (including rotation) for `tablet_name` via
+/// `settabletarea`.
+pub fn set_tablet_area(tablet_name: &str, area: &TabletArea) -> Result<()> {
+    let output = Command::new("OpenTabletDriver.Console.exe")
+        .args([
+            "settabletarea",
+            tablet_name,
+            &area.rect.width().to_string(),
+            &area.rect.height().to_string(),
+            &area.rect.center.x.to_string(),
+            &area.rect.center.y.to_string(),
+            &area.rotation.to_string(),
+        ])
+        .output()
+        .context("Failed to set tablet area")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("settabletarea failed: {}", stderr.trim());
+    }
+
+    Ok(())
+}
+
 /// Detect tablet name from OTD settings.json.
 pub fn detect_tablet_name() -> Result<String> {
     let local_app_data =
@@ -202,23 +299,16 @@ fn get_areas(tablet_name: &str) -> Result<(DisplayArea, TabletArea)> {
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let display = parse_area_from_output(&stdout, "Display area:")?;
-    let tablet = parse_area_from_output(&stdout, "Tablet area:")?;
-
-    Ok((
-        DisplayArea {
-            width: display.width,
-            height: display.height,
-            center_x: display.center_x,
-            center_y: display.center_y,
-        },
-        tablet,
-    ))
+    let (display, _) = parse_area_from_output(&stdout, "Display area:")?;
+    let (rect, rotation) = parse_area_from_output(&stdout, "Tablet area:")?;
+
+    Ok((display, TabletArea { rect, rotation }))
 }
 
 /// Parse an area line from getareas output.
 /// Format: `Display area: [5120x2160@<2560, 1080>:0°],`
-fn parse_area_from_output(output: &str, prefix: &str) -> Result<TabletArea> {
+/// Returns the area and its rotation in degrees.
+fn parse_area_from_output<U>(output: &str, prefix: &str) -> Result<(Rect<U>, f64)> {
     let line = output
         .lines()
         .find(|l| l.contains(prefix))
@@ -251,11 +341,9 @@ fn parse_area_from_output(output: &str, prefix: &str) -> Result<TabletArea> {
     let rotation_str = content[colon_pos + 1..].trim_end_matches('°');
     let rotation: f64 = rotation_str.parse().unwrap_or(0.0);
 
-    Ok(TabletArea {
-        width,
-        height,
-        center_x,
-        center_y,
-        rotation,
-    })
+    let area = Rect::new(Point::new(center_x, center_y), Size::new(width, height))
+        .validate()
+        .map_err(anyhow::Error::msg)
+        .with_context(|| format!("Invalid {prefix}"))?;
+    Ok((area, rotation))
 }
@@ -0,0 +1,604 @@
+use crate::geometry::{
+    self, Anchor, Clamp, DisplayArea, FitMode, Follow, Insets, IntRect, MappingMode, Point, Px,
+    Region, Size, SizeLimit, TabletArea, Zoom,
+};
+use crate::placement::{self, MonitorInfo, SpanPolicy, WorkAreaPolicy};
+
+/// How a window is mapped: everything from the command line that shapes the
+/// display area.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub mode: MappingMode,
+    pub scale: f64,
+    pub size: Option<Size<Px>>,
+    pub min_size: Option<SizeLimit>,
+    pub max_size: Option<SizeLimit>,
+    pub follow: Follow,
+    pub follow_scale: f64,
+    pub fit_mode: FitMode,
+    pub max_distortion: f64,
+    pub anchor: Anchor,
+    pub crop_anchor: Anchor,
+    pub span: SpanPolicy,
+    pub work_area: WorkAreaPolicy,
+    pub region: Region,
+    pub insets: Insets,
+    pub clamp: Clamp,
+}
+
+impl Default for Settings {
+    /// The command-line defaults.
+    fn default() -> Self {
+        Settings {
+            mode: MappingMode::Window,
+            scale: 1.0,
+            size: None,
+            min_size: None,
+            max_size: None,
+            follow: Follow::Monitor,
+            follow_scale: 0.5,
+            fit_mode: FitMode::Cover,
+            max_distortion: 0.0,
+            anchor: Anchor::Center,
+            crop_anchor: Anchor::Center,
+            span: SpanPolicy::Whole,
+            work_area: WorkAreaPolicy::Off,
+            region: Region::default(),
+            insets: Insets::default(),
+            clamp: Clamp::None,
+        }
+    }
+}
+
+impl Settings {
+    /// Where the tablet area is cropped to the display area's aspect ratio,
+    /// or `None` if these settings don't crop it.
+    pub fn crop(&self) -> Option<Anchor> {
+        (self.mode == MappingMode::Window && self.fit_mode == FitMode::Crop)
+            .then_some(self.crop_anchor)
+    }
+
+    /// Whether the display area depends on the cursor position.
+    pub fn follows_cursor(&self) -> bool {
+        self.mode == MappingMode::Cursor || self.span == SpanPolicy::Cursor
+    }
+}
+
+/// The desktop a window is mapped on, in screen coordinates.
+pub struct Scene<'a> {
+    /// Bounds of the target window.
+    pub window: IntRect,
+    pub maximized: bool,
+    pub monitors: &'a [MonitorInfo],
+    /// Cursor position, needed when the settings follow the cursor.
+    pub cursor: Option<Point<Px>>,
+}
+
+/// A display area computed by [`compute_area`], with what shaped it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mapping {
+    pub area: DisplayArea,
+    /// How far the tablet's aspect ratio was stretched to fit, as a fraction.
+    pub distortion: f64,
+    /// How `--min-size` or `--max-size` changed the area, if they did.
+    pub size_limit: Option<SizeLimited>,
+    /// `--region` and `--insets` left nothing of the window, so the whole
+    /// window was mapped instead.
+    pub trimmed_away: bool,
+    /// Where the cursor can move without changing the area, if the settings
+    /// follow the cursor.
+    pub cursor_cell: Option<CursorCell>,
+}
+
+/// The part of the screen the cursor can move within without changing a
+/// mapping that follows it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CursorCell {
+    /// The monitor the cursor is on, with `--span cursor`.
+    pub monitor: Option<IntRect>,
+    /// The area itself in cursor mode, which stays put until the cursor
+    /// leaves it.
+    pub area: Option<DisplayArea>,
+}
+
+impl CursorCell {
+    pub fn contains(&self, cursor: Point<Px>) -> bool {
+        self.monitor.is_none_or(|m| m.contains(cursor))
+            && self.area.is_none_or(|a| a.contains(cursor))
+    }
+}
+
+/// A display area resized to fit `--min-size` or `--max-size`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeLimited {
+    Enlarged { from: Size<Px>, to: Size<Px> },
+    Shrunk { from: Size<Px>, to: Size<Px> },
+}
+
+impl std::fmt::Display for SizeLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (from, to, limit, verb) = match self {
+            SizeLimited::Enlarged { from, to } => (from, to, "below the minimum", "enlarged"),
+            SizeLimited::Shrunk { from, to } => (from, to, "above the maximum", "shrunk"),
+        };
+        write!(
+            f,
+            "Display area {:.0}x{:.0} is {limit} size, {verb} to {:.0}x{:.0}",
+            from.width, from.height, to.width, to.height
+        )
+    }
+}
+
+/// The part of the screen the window is mapped within: the window after
+/// `--span` and `--work-area`, and the monitor holding it.
+fn frame<'a>(settings: &Settings, scene: &Scene<'a>) -> (IntRect, Option<&'a MonitorInfo>) {
+    let window_rect =
+        placement::span_rect(scene.window, scene.monitors, settings.span, scene.cursor);
+
+    // The monitor holding (the mapped part of) the window
+    let monitor = placement::largest_overlap(window_rect, scene.monitors);
+    let window_rect = match monitor {
+        Some(monitor) => {
+            placement::usable_rect(window_rect, scene.maximized, monitor, settings.work_area)
+        }
+        None => window_rect,
+    };
+    (window_rect, monitor)
+}
+
+/// The rect precision zoom is applied within, which [`Zoom`] focus points
+/// are relative to.
+pub fn zoom_frame(settings: &Settings, scene: &Scene) -> IntRect {
+    frame(settings, scene).0
+}
+
+/// Compute the display area for the scene's window in screen coordinates.
+///
+/// `tablet` is the active tablet area with its rotation applied, `zoom` the
+/// precision zoom if it is on, and `previous` the area currently mapped,
+/// which the follow-cursor area keeps while the cursor stays inside it.
+/// Returns `None` if there is nothing to map.
+pub fn compute_area(
+    settings: &Settings,
+    tablet: &TabletArea,
+    scene: &Scene,
+    zoom: Option<&Zoom>,
+    previous: Option<&DisplayArea>,
+) -> Option<Mapping> {
+    let tablet_aspect_ratio = tablet.rect.aspect_ratio();
+    let (window_rect, monitor) = frame(settings, scene);
+    // Mapping nothing would leave the previous window's mapping in place
+    let trimmed = geometry::apply_region(window_rect, &settings.region)
+        .and_then(|rect| geometry::apply_insets(rect, &settings.insets));
+    let (rect, trimmed_away) = match trimmed {
+        Some(rect) => (rect, false),
+        None if !window_rect.is_empty() => (window_rect, true),
+        None => return None,
+    };
+
+    let bounds = match settings.clamp {
+        Clamp::None => None,
+        // A window on no monitor has no visible part to map
+        Clamp::Monitor => Some(monitor?.bounds),
+        Clamp::Desktop => scene
+            .monitors
+            .iter()
+            .map(|m| m.bounds)
+            .reduce(|a, b| a.union(&b)),
+    };
+
+    // Only the visible part of the window counts when clamping
+    let rect = match bounds {
+        Some(bounds) => rect.intersect(&bounds)?,
+        None => rect,
+    };
+
+    let (area, distortion) = match settings.mode {
+        MappingMode::Window => {
+            let fit = geometry::fit_to_window(
+                rect,
+                tablet_aspect_ratio,
+                settings.fit_mode,
+                settings.max_distortion,
+            )?;
+
+            let strip = geometry::letterbox(rect, &fit.area);
+            if strip.width > 0.0 || strip.height > 0.0 {
+                log::debug!(
+                    "Letterboxed: {:.0}px left/right, {:.0}px top/bottom unreachable",
+                    strip.width,
+                    strip.height
+                );
+            }
+            (fit.area, fit.distortion)
+        }
+        MappingMode::Physical => {
+            let density = placement::largest_overlap(rect, scene.monitors).and_then(|m| m.density);
+            let Some(density) = density else {
+                log::debug!("Monitor reports no physical size — skipping 1:1 mapping");
+                return None;
+            };
+            // Keeps the tablet's shape
+            let area = geometry::physical_area(rect, tablet, density, settings.scale)?;
+            (area, 0.0)
+        }
+        MappingMode::Fixed => {
+            let area = geometry::fixed_area(rect, settings.size?)?;
+            (area, area.distortion(tablet_aspect_ratio))
+        }
+        // Follows the cursor rather than the window, so anchoring, zoom and
+        // clamping don't apply
+        MappingMode::Cursor => {
+            let cursor = scene.cursor?;
+            let container = match settings.follow {
+                Follow::Monitor => {
+                    scene
+                        .monitors
+                        .iter()
+                        .find(|m| m.bounds.contains(cursor))?
+                        .bounds
+                }
+                Follow::Window => rect,
+            };
+            let area = geometry::follow_cursor(
+                previous,
+                container,
+                tablet_aspect_ratio,
+                settings.follow_scale,
+                cursor,
+            )?;
+            return Some(Mapping {
+                area,
+                distortion: 0.0,
+                size_limit: None,
+                trimmed_away,
+                cursor_cell: cursor_cell(settings, scene, &area),
+            });
+        }
+    };
+
+    // Percentage limits are relative to the monitor holding the window
+    let resolve = |limit: Option<SizeLimit>| match limit? {
+        SizeLimit::Pixels(size) => Some(size),
+        limit => Some(limit.resolve(monitor?.bounds)),
+    };
+    let limited = geometry::limit_size(
+        &area,
+        rect,
+        resolve(settings.min_size),
+        resolve(settings.max_size),
+    );
+    let size_limit = if limited.width() > area.width() {
+        Some(SizeLimited::Enlarged {
+            from: area.size,
+            to: limited.size,
+        })
+    } else if limited.width() < area.width() {
+        Some(SizeLimited::Shrunk {
+            from: area.size,
+            to: limited.size,
+        })
+    } else {
+        None
+    };
+
+    // Fixed and physical areas stay centered on the window
+    let area = match settings.mode {
+        MappingMode::Window => geometry::anchor_to_window(&limited, rect, settings.anchor),
+        _ => limited,
+    };
+
+    let area = match zoom {
+        Some(zoom) => geometry::zoom_area(&area, window_rect, zoom),
+        None => area,
+    };
+
+    let area = match bounds {
+        Some(bounds) => geometry::clamp_to_bounds(&area, bounds),
+        None => area,
+    };
+    Some(Mapping {
+        area,
+        distortion,
+        size_limit,
+        trimmed_away,
+        cursor_cell: cursor_cell(settings, scene, &area),
+    })
+}
+
+/// Where the cursor can move without changing `area`. Returns `None` if the
+/// settings don't follow the cursor, or it is on no monitor.
+fn cursor_cell(settings: &Settings, scene: &Scene, area: &DisplayArea) -> Option<CursorCell> {
+    if !settings.follows_cursor() {
+        return None;
+    }
+    let cursor = scene.cursor?;
+    let monitor = match settings.span {
+        SpanPolicy::Cursor => Some(
+            scene
+                .monitors
+                .iter()
+                .find(|m| m.bounds.contains(cursor))?
+                .bounds,
+        ),
+        _ => None,
+    };
+    Some(CursorCell {
+        monitor,
+        area: (settings.mode == MappingMode::Cursor).then_some(*area),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{PixelDensity, Rect};
+
+    /// 1920x1080 on the left, 2560x1440 on the right, both with a 48px
+    /// taskbar.
+    const MONITORS: [MonitorInfo; 2] = [
+        MonitorInfo {
+            bounds: IntRect::from_size(0, 0, 1920, 1080),
+            work_area: IntRect::from_size(0, 0, 1920, 1032),
+            density: None,
+        },
+        MonitorInfo {
+            bounds: IntRect::from_size(1920, 0, 2560, 1440),
+            work_area: IntRect::from_size(1920, 0, 2560, 1392),
+            density: None,
+        },
+    ];
+
+    fn settings() -> Settings {
+        Settings {
+            fit_mode: FitMode::Contain,
+            ..Settings::default()
+        }
+    }
+
+    /// 160x100mm tablet area (16:10), unrotated.
+    fn tablet() -> TabletArea {
+        TabletArea {
+            rect: Rect::new(Point::new(80.0, 50.0), Size::new(160.0, 100.0)),
+            rotation: 0.0,
+        }
+    }
+
+    fn scene(window: IntRect) -> Scene<'static> {
+        Scene {
+            window,
+            maximized: false,
+            monitors: &MONITORS,
+            cursor: None,
+        }
+    }
+
+    #[test]
+    fn contain_fits_window() {
+        let area = compute_area(
+            &settings(),
+            &tablet(),
+            &scene(IntRect::from_size(100, 100, 1600, 800)),
+            None,
+            None,
+        )
+        .unwrap()
+        .area;
+        assert_eq!(area.center, Point::new(900.0, 500.0));
+        assert_eq!(area.size, Size::new(1280.0, 800.0));
+    }
+
+    #[test]
+    fn maximized_window_maps_work_area() {
+        let settings = Settings {
+            fit_mode: FitMode::Stretch,
+            work_area: WorkAreaPolicy::Maximized,
+            ..settings()
+        };
+        let maximized = Scene {
+            maximized: true,
+            ..scene(IntRect::from_size(1912, -8, 2576, 1456))
+        };
+        let mapping = compute_area(&settings, &tablet(), &maximized, None, None).unwrap();
+        assert_eq!(mapping.area.size, Size::new(2560.0, 1392.0));
+        assert_eq!(mapping.area.center, Point::new(3200.0, 696.0));
+        assert!((mapping.distortion - (2560.0 / 1392.0 / 1.6 - 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn physical_mode_uses_density_of_window_monitor() {
+        let mut monitors = MONITORS;
+        monitors[1].density = Some(PixelDensity::from_dpi(254.0, 254.0));
+        let settings = Settings {
+            mode: MappingMode::Physical,
+            ..settings()
+        };
+        let right = Scene {
+            monitors: &monitors,
+            ..scene(IntRect::from_size(2000, 100, 2000, 1200))
+        };
+        let area = compute_area(&settings, &tablet(), &right, None, None)
+            .unwrap()
+            .area;
+        assert!((area.width() - 1600.0).abs() < 1e-9);
+        assert!((area.height() - 1000.0).abs() < 1e-9);
+
+        // The left monitor doesn't report a physical size
+        let left = Scene {
+            monitors: &monitors,
+            ..scene(IntRect::from_size(100, 100, 800, 600))
+        };
+        assert_eq!(compute_area(&settings, &tablet(), &left, None, None), None);
+    }
+
+    #[test]
+    fn desktop_clamp_keeps_area_on_monitors() {
+        let settings = Settings {
+            fit_mode: FitMode::Cover,
+            clamp: Clamp::Desktop,
+            ..settings()
+        };
+        // Hangs off the left edge of the desktop
+        let area = compute_area(
+            &settings,
+            &tablet(),
+            &scene(IntRect::from_size(-400, 200, 1200, 600)),
+            None,
+            None,
+        )
+        .unwrap()
+        .area;
+        assert!(area.left() >= 0.0, "{area:?}");
+    }
+
+    #[test]
+    fn tiny_window_is_enlarged_to_minimum_size() {
+        let settings = Settings {
+            min_size: Some(SizeLimit::Percent(25.0)),
+            max_size: Some(SizeLimit::Pixels(Size::new(1280.0, 1280.0))),
+            ..settings()
+        };
+        // 25% of the 2560x1440 monitor is 640x360, so 16:10 needs 640x400
+        let tiny = IntRect::from_size(3000, 600, 80, 50);
+        let area = compute_area(&settings, &tablet(), &scene(tiny), None, None)
+            .unwrap()
+            .area;
+        assert_eq!(area.size, Size::new(640.0, 400.0));
+        assert_eq!(area.center, tiny.to_rect().center);
+
+        let large = IntRect::from_size(1920, 0, 2560, 1440);
+        let mapping = compute_area(&settings, &tablet(), &scene(large), None, None).unwrap();
+        assert_eq!(mapping.area.size, Size::new(1280.0, 800.0));
+        assert_eq!(
+            mapping.size_limit,
+            Some(SizeLimited::Shrunk {
+                from: Size::new(2304.0, 1440.0),
+                to: Size::new(1280.0, 800.0)
+            })
+        );
+    }
+
+    #[test]
+    fn window_too_small_for_insets_is_mapped_whole() {
+        let settings = Settings {
+            fit_mode: FitMode::Stretch,
+            insets: "300".parse().unwrap(),
+            ..settings()
+        };
+        let window = IntRect::from_size(100, 100, 800, 600);
+        let mapping = compute_area(&settings, &tablet(), &scene(window), None, None).unwrap();
+        assert!(mapping.trimmed_away);
+        assert_eq!(mapping.area, window.to_rect());
+
+        let large = IntRect::from_size(100, 100, 1600, 900);
+        let mapping = compute_area(&settings, &tablet(), &scene(large), None, None).unwrap();
+        assert!(!mapping.trimmed_away);
+        assert_eq!(mapping.area.size, Size::new(1000.0, 300.0));
+    }
+
+    #[test]
+    fn fixed_area_ignores_anchor() {
+        let settings = Settings {
+            mode: MappingMode::Fixed,
+            size: Some(Size::new(1200.0, 750.0)),
+            anchor: "top-left".parse().unwrap(),
+            ..settings()
+        };
+        let window = IntRect::from_size(100, 100, 1600, 900);
+        let area = compute_area(&settings, &tablet(), &scene(window), None, None)
+            .unwrap()
+            .area;
+        assert_eq!(area.center, window.to_rect().center);
+        assert_eq!(area.size, Size::new(1200.0, 750.0));
+    }
+
+    #[test]
+    fn zoom_centers_on_cursor_in_spanned_window() {
+        let settings = Settings {
+            fit_mode: FitMode::Stretch,
+            span: SpanPolicy::Largest,
+            ..settings()
+        };
+        // Mostly on the right monitor, so only that part is mapped
+        let spanning = scene(IntRect::from_size(1520, 100, 2400, 1000));
+        let cursor = Point::new(2500.0, 500.0);
+        let zoom = Zoom::focused_on(0.25, zoom_frame(&settings, &spanning), cursor);
+        let area = compute_area(&settings, &tablet(), &spanning, Some(&zoom), None)
+            .unwrap()
+            .area;
+        assert_eq!(area.center, cursor);
+        assert_eq!(area.size, Size::new(500.0, 250.0));
+    }
+
+    #[test]
+    fn cursor_mode_needs_cursor() {
+        let settings = Settings {
+            mode: MappingMode::Cursor,
+            ..settings()
+        };
+        let window = IntRect::from_size(100, 100, 800, 600);
+        assert_eq!(
+            compute_area(&settings, &tablet(), &scene(window), None, None),
+            None
+        );
+
+        let with_cursor = Scene {
+            cursor: Some(Point::new(960.0, 540.0)),
+            ..scene(window)
+        };
+        let area = compute_area(&settings, &tablet(), &with_cursor, None, None)
+            .unwrap()
+            .area;
+        assert_eq!(area.center, Point::new(960.0, 540.0));
+    }
+
+    #[test]
+    fn cursor_cell_covers_moves_that_keep_the_area() {
+        let window = IntRect::from_size(1520, 100, 880, 900);
+        let at = |x, y| Scene {
+            cursor: Some(Point::new(x, y)),
+            ..scene(window)
+        };
+        let cell = |settings: &Settings, scene: &Scene| {
+            compute_area(settings, &tablet(), scene, None, None)
+                .unwrap()
+                .cursor_cell
+        };
+
+        assert_eq!(cell(&settings(), &at(500.0, 500.0)), None);
+
+        // Only crossing onto another monitor moves a spanned window's area
+        let spanned = Settings {
+            span: SpanPolicy::Cursor,
+            ..settings()
+        };
+        let left = cell(&spanned, &at(1700.0, 500.0)).unwrap();
+        assert!(left.contains(Point::new(100.0, 100.0)));
+        assert!(!left.contains(Point::new(1920.0, 500.0)));
+        assert_eq!(
+            cell(
+                &spanned,
+                &Scene {
+                    cursor: None,
+                    ..at(0.0, 0.0)
+                }
+            ),
+            None
+        );
+
+        // The area stays put until the cursor leaves it
+        let follow = Settings {
+            mode: MappingMode::Cursor,
+            ..settings()
+        };
+        let scene = at(960.0, 540.0);
+        let mapping = compute_area(&follow, &tablet(), &scene, None, None).unwrap();
+        let cell = mapping.cursor_cell.unwrap();
+        assert!(cell.contains(Point::new(1100.0, 600.0)));
+        assert!(!cell.contains(Point::new(1900.0, 1000.0)));
+
+        let moved = at(1100.0, 600.0);
+        let kept = compute_area(&follow, &tablet(), &moved, None, Some(&mapping.area)).unwrap();
+        assert_eq!(kept.area, mapping.area);
+    }
+}
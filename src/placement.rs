@@ -0,0 +1,353 @@
+use crate::geometry::{IntRect, PixelDensity, Point, Px};
+
+/// Slack in pixels when checking whether a window edge lines up with a work
+/// area edge. DWM frame bounds can be off by a pixel or two.
+const EDGE_TOLERANCE: i32 = 8;
+
+/// A monitor's full bounds and its work area (the bounds minus the taskbar
+/// and any docked app bars), in screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonitorInfo {
+    pub bounds: IntRect,
+    pub work_area: IntRect,
+    /// Physical pixel density, or `None` if the monitor doesn't report a
+    /// physical size.
+    pub density: Option<PixelDensity>,
+}
+
+/// Which windows are mapped to the monitor work area instead of their own
+/// bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WorkAreaPolicy {
+    /// Always map the window's own bounds.
+    #[default]
+    Off,
+    /// Maximized windows.
+    Maximized,
+    /// Maximized windows and windows snapped to the edges of the work area.
+    Snapped,
+}
+
+impl std::str::FromStr for WorkAreaPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "maximized" => Ok(Self::Maximized),
+            "snapped" => Ok(Self::Snapped),
+            _ => Err(format!(
+                "'{s}' is not a work area policy (expected off, maximized, or snapped)"
+            )),
+        }
+    }
+}
+
+/// Which part of a window spanning several monitors is mapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpanPolicy {
+    /// The whole window, across every monitor it touches.
+    #[default]
+    Whole,
+    /// The part on the monitor holding the largest share of the window.
+    Largest,
+    /// The part on the monitor the cursor is on.
+    Cursor,
+}
+
+impl std::str::FromStr for SpanPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "whole" => Ok(Self::Whole),
+            "largest" => Ok(Self::Largest),
+            "cursor" => Ok(Self::Cursor),
+            _ => Err(format!(
+                "'{s}' is not a span policy (expected whole, largest, or cursor)"
+            )),
+        }
+    }
+}
+
+/// The monitor holding the largest share of `window`, or `None` if it is on
+/// no monitor at all.
+pub fn largest_overlap(window: IntRect, monitors: &[MonitorInfo]) -> Option<&MonitorInfo> {
+    monitors
+        .iter()
+        .map(|m| (m, window.intersect(&m.bounds).map_or(0, |r| r.area())))
+        .filter(|&(_, overlap)| overlap > 0)
+        .max_by_key(|&(_, overlap)| overlap)
+        .map(|(m, _)| m)
+}
+
+/// The part of `window` to map under `policy`.
+///
+/// With [`SpanPolicy::Largest`] and [`SpanPolicy::Cursor`] the window is cut
+/// to the bounds of a single monitor. If the cursor is unknown or not on a
+/// monitor the window touches, the monitor with the largest share is used
+/// instead. A window on no monitor at all is returned unchanged.
+pub fn span_rect(
+    window: IntRect,
+    monitors: &[MonitorInfo],
+    policy: SpanPolicy,
+    cursor: Option<Point<Px>>,
+) -> IntRect {
+    let monitor = match policy {
+        SpanPolicy::Whole => return window,
+        SpanPolicy::Largest => largest_overlap(window, monitors),
+        SpanPolicy::Cursor => cursor
+            .and_then(|cursor| monitors.iter().find(|m| m.bounds.contains(cursor)))
+            .filter(|m| window.intersect(&m.bounds).is_some())
+            .or_else(|| largest_overlap(window, monitors)),
+    };
+
+    monitor
+        .and_then(|m| window.intersect(&m.bounds))
+        .unwrap_or(window)
+}
+
+/// True if `window` sits inside the work area with two of its edges lined up
+/// with it — adjacent edges for a quarter, opposite edges for a half or a
+/// column — as Snap leaves it.
+pub fn is_snapped(window: IntRect, monitor: &MonitorInfo) -> bool {
+    let work = monitor.work_area;
+    let near = |a: i32, b: i32| (a - b).abs() <= EDGE_TOLERANCE;
+
+    let inside = window.left >= work.left - EDGE_TOLERANCE
+        && window.top >= work.top - EDGE_TOLERANCE
+        && window.right <= work.right + EDGE_TOLERANCE
+        && window.bottom <= work.bottom + EDGE_TOLERANCE;
+    let (left, right) = (near(window.left, work.left), near(window.right, work.right));
+    let (top, bottom) = (near(window.top, work.top), near(window.bottom, work.bottom));
+    let corner = (left || right) && (top || bottom);
+
+    inside && (corner || (left && right) || (top && bottom))
+}
+
+/// The part of the screen to map for `window` on `monitor`.
+///
+/// Where `policy` applies, the window is cut to the monitor's work area, so a
+/// maximized window whose frame reaches behind the taskbar or onto the next
+/// monitor maps to the usable part of its own monitor only. Otherwise the
+/// window is returned unchanged.
+pub fn usable_rect(
+    window: IntRect,
+    maximized: bool,
+    monitor: &MonitorInfo,
+    policy: WorkAreaPolicy,
+) -> IntRect {
+    let applies = match policy {
+        WorkAreaPolicy::Off => false,
+        WorkAreaPolicy::Maximized => maximized,
+        WorkAreaPolicy::Snapped => maximized || is_snapped(window, monitor),
+    };
+    if !applies {
+        return window;
+    }
+
+    window
+        .intersect(&monitor.work_area)
+        .unwrap_or(monitor.work_area)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1920x1080 primary monitor with a 48px taskbar along the bottom.
+    const PRIMARY: MonitorInfo = MonitorInfo {
+        bounds: IntRect::from_size(0, 0, 1920, 1080),
+        work_area: IntRect::from_size(0, 0, 1920, 1032),
+        density: None,
+    };
+
+    #[test]
+    fn maximized_window_maps_to_work_area() {
+        // DWM bounds of a maximized window overhang by a few pixels and reach
+        // behind the taskbar
+        let window = IntRect::from_size(-8, -8, 1936, 1096);
+        for policy in [WorkAreaPolicy::Maximized, WorkAreaPolicy::Snapped] {
+            assert_eq!(
+                usable_rect(window, true, &PRIMARY, policy),
+                PRIMARY.work_area
+            );
+        }
+    }
+
+    #[test]
+    fn off_keeps_window_bounds() {
+        let window = IntRect::from_size(-8, -8, 1936, 1096);
+        assert_eq!(
+            usable_rect(window, true, &PRIMARY, WorkAreaPolicy::Off),
+            window
+        );
+    }
+
+    #[test]
+    fn maximized_window_overlapping_neighbour_is_cut_to_own_monitor() {
+        // Secondary monitor to the right with a taskbar on its left edge
+        let secondary = MonitorInfo {
+            bounds: IntRect::from_size(1920, 0, 2560, 1440),
+            work_area: IntRect::from_size(1980, 0, 2500, 1440),
+            density: None,
+        };
+        let window = IntRect::from_size(1900, -8, 2596, 1456);
+        assert_eq!(
+            usable_rect(window, true, &secondary, WorkAreaPolicy::Maximized),
+            secondary.work_area
+        );
+    }
+
+    #[test]
+    fn snapped_halves_and_quarters_are_detected() {
+        let snapped = [
+            IntRect::from_size(0, 0, 960, 1032),    // left half
+            IntRect::from_size(960, 0, 960, 1032),  // right half
+            IntRect::from_size(0, 0, 960, 516),     // top-left quarter
+            IntRect::from_size(960, 516, 960, 516), // bottom-right quarter
+            IntRect::from_size(640, 0, 640, 1032),  // middle column
+        ];
+        for window in snapped {
+            assert!(is_snapped(window, &PRIMARY), "{window:?}");
+        }
+
+        let floating = [
+            IntRect::from_size(200, 150, 800, 600),
+            IntRect::from_size(0, 200, 800, 600), // touches the left edge only
+            IntRect::from_size(-300, 0, 960, 1032), // hangs off the monitor
+        ];
+        for window in floating {
+            assert!(!is_snapped(window, &PRIMARY), "{window:?}");
+        }
+    }
+
+    #[test]
+    fn snapped_window_only_maps_work_area_with_snapped_policy() {
+        // Left half whose frame reaches a few pixels behind the taskbar
+        let window = IntRect::from_size(0, 0, 960, 1036);
+        assert_eq!(
+            usable_rect(window, false, &PRIMARY, WorkAreaPolicy::Maximized),
+            window
+        );
+        assert_eq!(
+            usable_rect(window, false, &PRIMARY, WorkAreaPolicy::Snapped),
+            IntRect::from_size(0, 0, 960, 1032)
+        );
+    }
+
+    #[test]
+    fn floating_window_is_unchanged() {
+        let window = IntRect::from_size(200, 150, 800, 600);
+        assert_eq!(
+            usable_rect(window, false, &PRIMARY, WorkAreaPolicy::Snapped),
+            window
+        );
+    }
+
+    /// 1920x1080 on the left, 2560x1440 on the right, tops aligned.
+    const MONITORS: [MonitorInfo; 2] = [
+        MonitorInfo {
+            bounds: IntRect::from_size(0, 0, 1920, 1080),
+            work_area: IntRect::from_size(0, 0, 1920, 1032),
+            density: None,
+        },
+        MonitorInfo {
+            bounds: IntRect::from_size(1920, 0, 2560, 1440),
+            work_area: IntRect::from_size(1920, 0, 2560, 1392),
+            density: None,
+        },
+    ];
+
+    #[test]
+    fn whole_policy_keeps_spanning_window() {
+        let window = IntRect::from_size(1400, 100, 1000, 800);
+        let cursor = Some(Point::new(2000.0, 500.0));
+        assert_eq!(
+            span_rect(window, &MONITORS, SpanPolicy::Whole, cursor),
+            window
+        );
+    }
+
+    #[test]
+    fn largest_policy_picks_monitor_with_most_of_the_window() {
+        // 520px on the left monitor, 480px on the right
+        let window = IntRect::from_size(1400, 100, 1000, 800);
+        assert_eq!(
+            span_rect(window, &MONITORS, SpanPolicy::Largest, None),
+            IntRect::from_size(1400, 100, 520, 800)
+        );
+
+        // Mostly on the right monitor, and taller than the left one
+        let window = IntRect::from_size(1700, 200, 1000, 1100);
+        assert_eq!(
+            span_rect(window, &MONITORS, SpanPolicy::Largest, None),
+            IntRect::from_size(1920, 200, 780, 1100)
+        );
+    }
+
+    #[test]
+    fn cursor_policy_follows_the_cursor() {
+        let window = IntRect::from_size(1400, 100, 1000, 800);
+        assert_eq!(
+            span_rect(
+                window,
+                &MONITORS,
+                SpanPolicy::Cursor,
+                Some(Point::new(2000.0, 500.0))
+            ),
+            IntRect::from_size(1920, 100, 480, 800)
+        );
+        assert_eq!(
+            span_rect(
+                window,
+                &MONITORS,
+                SpanPolicy::Cursor,
+                Some(Point::new(1500.0, 500.0))
+            ),
+            IntRect::from_size(1400, 100, 520, 800)
+        );
+    }
+
+    #[test]
+    fn cursor_elsewhere_falls_back_to_largest_share() {
+        // Cursor on the right monitor, which the window doesn't touch
+        let window = IntRect::from_size(100, 100, 1000, 800);
+        let cursor = Some(Point::new(3000.0, 1200.0));
+        assert_eq!(
+            span_rect(window, &MONITORS, SpanPolicy::Cursor, cursor),
+            window
+        );
+
+        let window = IntRect::from_size(1400, 100, 1000, 800);
+        assert_eq!(
+            span_rect(window, &MONITORS, SpanPolicy::Cursor, None),
+            IntRect::from_size(1400, 100, 520, 800)
+        );
+    }
+
+    #[test]
+    fn window_off_every_monitor_is_unchanged() {
+        let window = IntRect::from_size(-2000, 100, 800, 600);
+        assert!(largest_overlap(window, &MONITORS).is_none());
+        for policy in [SpanPolicy::Largest, SpanPolicy::Cursor] {
+            assert_eq!(span_rect(window, &MONITORS, policy, None), window);
+        }
+    }
+
+    #[test]
+    fn parse_span_policy() {
+        assert_eq!("whole".parse(), Ok(SpanPolicy::Whole));
+        assert_eq!("Largest".parse(), Ok(SpanPolicy::Largest));
+        assert_eq!("CURSOR".parse(), Ok(SpanPolicy::Cursor));
+        assert!("primary".parse::<SpanPolicy>().is_err());
+    }
+
+    #[test]
+    fn parse_work_area_policy() {
+        assert_eq!("off".parse(), Ok(WorkAreaPolicy::Off));
+        assert_eq!("Maximized".parse(), Ok(WorkAreaPolicy::Maximized));
+        assert_eq!("SNAPPED".parse(), Ok(WorkAreaPolicy::Snapped));
+        assert!("always".parse::<WorkAreaPolicy>().is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use crate::MappingArgs;
+use crate::geometry::{
+    self, DesktopSpace, DisplayArea, IntRect, Mm, PixelDensity, Point, Px, Rect, Size, TabletArea,
+};
+use crate::pipeline::{self, Scene};
+use crate::placement::MonitorInfo;
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Blank space around the drawing, in screen pixels.
+const SVG_MARGIN: f64 = 40.0;
+
+#[derive(clap::Args)]
+pub struct PreviewArgs {
+    /// Window bounds in screen pixels, as LEFT,TOP,WIDTH,HEIGHT
+    /// (100,100,1600,900).
+    #[arg(long, allow_hyphen_values = true)]
+    window: IntRect,
+
+    /// Monitor bounds in screen pixels, as LEFT,TOP,WIDTH,HEIGHT. Repeat for
+    /// each monitor (--monitor 0,0,1920,1080 --monitor 1920,0,2560,1440).
+    #[arg(
+        long = "monitor",
+        default_value = "0,0,1920,1080",
+        allow_hyphen_values = true
+    )]
+    monitors: Vec<IntRect>,
+
+    /// Height of the taskbar along the bottom of each monitor in pixels,
+    /// which --work-area leaves out.
+    #[arg(long, default_value_t = 0)]
+    taskbar: i32,
+
+    /// Monitor DPI used by `--mode physical`.
+    #[arg(long, default_value_t = 96.0, value_parser = parse_dpi)]
+    dpi: f64,
+
+    /// Treat the window as maximized.
+    #[arg(long)]
+    maximized: bool,
+
+    /// Cursor position in screen pixels, as X,Y. Needed by `--mode cursor`
+    /// and `--span cursor`.
+    #[arg(long, allow_hyphen_values = true)]
+    cursor: Option<Point<Px>>,
+
+    /// Full tablet area in millimetres, as WIDTHxHEIGHT.
+    #[arg(long, default_value = "152x95")]
+    tablet_area: Size<Mm>,
+
+    /// Where to write the SVG drawing.
+    #[arg(long, default_value = "inkbound-preview.svg")]
+    svg: PathBuf,
+
+    #[command(flatten)]
+    mapping: MappingArgs,
+}
+
+/// Run the mapping pipeline on the given layout and report the result.
+pub fn run(args: &PreviewArgs) -> Result<()> {
+    let settings = args.mapping.settings()?;
+
+    // The tablet area as OTD would report it: the full area, unrotated
+    let size = args.tablet_area;
+    let full_area = TabletArea {
+        rect: Rect::new(Point::new(size.width / 2.0, size.height / 2.0), size),
+        rotation: 0.0,
+    };
+    let active_area = match &args.mapping.tablet_region {
+        Some(region) => geometry::restrict_tablet_area(&full_area, region).with_context(|| {
+            format!(
+                "Tablet region doesn't fit the {:.1}x{:.1}mm tablet area",
+                size.width, size.height
+            )
+        })?,
+        None => full_area,
+    };
+    let tablet = geometry::rotate_tablet_area(&active_area, args.mapping.rotation);
+
+    let density = PixelDensity::from_dpi(args.dpi, args.dpi);
+    let monitors: Vec<MonitorInfo> = args
+        .monitors
+        .iter()
+        .map(|&bounds| MonitorInfo {
+            bounds,
+            work_area: IntRect {
+                bottom: bounds.bottom - args.taskbar,
+                ..bounds
+            },
+            density: Some(density),
+        })
+        .collect();
+    let scene = Scene {
+        window: args.window,
+        maximized: args.maximized,
+        monitors: &monitors,
+        cursor: args.cursor,
+    };
+
+    let mapping = pipeline::compute_area(&settings, &tablet, &scene, None, None).context(
+        "Nothing to map: the window is off screen or empty, or --mode cursor has no --cursor",
+    )?;
+    let area = mapping.area;
+    let otd_area = DesktopSpace::from_monitors(&args.monitors).to_otd(&area);
+    let tablet = match settings.crop() {
+        Some(anchor) => geometry::crop_tablet_area(&tablet, area.aspect_ratio(), anchor),
+        None => tablet,
+    };
+
+    println!(
+        "Window:       {}x{} at ({}, {})",
+        args.window.width(),
+        args.window.height(),
+        args.window.left,
+        args.window.top
+    );
+    println!("Display area: {} (screen)", format_area(&area));
+    println!("              {} (OTD)", format_area(&otd_area));
+    println!(
+        "Tablet area:  [{:.1}x{:.1}mm@<{:.1}, {:.1}>:{}°]",
+        tablet.rect.width(),
+        tablet.rect.height(),
+        tablet.rect.center.x,
+        tablet.rect.center.y,
+        tablet.rotation
+    );
+    println!("Stretch:      {:.1}%", mapping.distortion * 100.0);
+    if mapping.trimmed_away {
+        println!("The window is too small for --region and --insets, so all of it is mapped");
+    }
+    if let Some(size_limit) = mapping.size_limit {
+        println!("{size_limit}");
+    }
+
+    std::fs::write(&args.svg, render_svg(&scene, &area))
+        .with_context(|| format!("Failed to write {}", args.svg.display()))?;
+    println!("Wrote {}", args.svg.display());
+    Ok(())
+}
+
+fn parse_dpi(s: &str) -> Result<f64, String> {
+    let v: f64 = s
+        .parse()
+        .map_err(|_| format!("'{s}' is not a valid number"))?;
+    if !v.is_finite() || v <= 0.0 {
+        return Err("DPI must be greater than 0".to_string());
+    }
+    Ok(v)
+}
+
+fn format_area(area: &DisplayArea) -> String {
+    format!(
+        "[{:.0}x{:.0}@<{:.0}, {:.0}>]",
+        area.width(),
+        area.height(),
+        area.center.x,
+        area.center.y
+    )
+}
+
+/// Draw the monitors, the window and the mapped area in screen coordinates.
+fn render_svg(scene: &Scene, area: &DisplayArea) -> String {
+    let mut left = area.left().min(scene.window.left as f64);
+    let mut top = area.top().min(scene.window.top as f64);
+    let mut right = area.right().max(scene.window.right as f64);
+    let mut bottom = area.bottom().max(scene.window.bottom as f64);
+    for m in scene.monitors {
+        left = left.min(m.bounds.left as f64);
+        top = top.min(m.bounds.top as f64);
+        right = right.max(m.bounds.right as f64);
+        bottom = bottom.max(m.bounds.bottom as f64);
+    }
+    let (left, top) = (left - SVG_MARGIN, top - SVG_MARGIN);
+    let (width, height) = (right - left + SVG_MARGIN, bottom - top + SVG_MARGIN);
+    let font_size = width.max(height) / 60.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{left} {top} {width} {height}" font-family="sans-serif" font-size="{font_size:.0}">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{left}" y="{top}" width="{width}" height="{height}" fill="white"/>"#
+    );
+
+    for (i, m) in scene.monitors.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r##"<rect {} fill="#e8e8e8" stroke="#808080" stroke-width="2" vector-effect="non-scaling-stroke"/>"##,
+            rect_attrs(&m.bounds.to_rect())
+        );
+        if m.work_area != m.bounds {
+            let _ = writeln!(
+                svg,
+                r##"<rect {} fill="none" stroke="#808080" stroke-dasharray="6 4" vector-effect="non-scaling-stroke"/>"##,
+                rect_attrs(&m.work_area.to_rect())
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r##"<text x="{}" y="{}" fill="#606060">Monitor {}</text>"##,
+            m.bounds.left as f64 + font_size / 2.0,
+            m.bounds.top as f64 + font_size * 1.5,
+            i + 1
+        );
+    }
+
+    let _ = writeln!(
+        svg,
+        r##"<rect {} fill="none" stroke="#2060c0" stroke-width="3" vector-effect="non-scaling-stroke"/>"##,
+        rect_attrs(&scene.window.to_rect())
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect {} fill="#e04040" fill-opacity="0.25" stroke="#e04040" stroke-width="2" vector-effect="non-scaling-stroke"/>"##,
+        rect_attrs(area)
+    );
+    let _ = writeln!(
+        svg,
+        r##"<text x="{}" y="{}" fill="#c02020">{:.0}x{:.0}</text>"##,
+        area.left() + font_size / 2.0,
+        area.bottom() - font_size / 2.0,
+        area.width(),
+        area.height()
+    );
+
+    if let Some(cursor) = scene.cursor {
+        let _ = writeln!(
+            svg,
+            r##"<circle cx="{}" cy="{}" r="{}" fill="#202020"/>"##,
+            cursor.x,
+            cursor.y,
+            font_size / 3.0
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn rect_attrs(rect: &Rect<Px>) -> String {
+    format!(
+        r#"x="{}" y="{}" width="{}" height="{}""#,
+        rect.left(),
+        rect.top(),
+        rect.width(),
+        rect.height()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dpi_must_be_positive() {
+        assert_eq!(parse_dpi("144"), Ok(144.0));
+        for s in ["0", "-96", "NaN", "inf", "high"] {
+            assert!(parse_dpi(s).is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn area_is_formatted_like_otd() {
+        let area = Rect::new(Point::new(960.4, 539.6), Size::new(1727.8, 1080.0));
+        assert_eq!(format_area(&area), "[1728x1080@<960, 540>]");
+    }
+
+    #[test]
+    fn svg_draws_monitors_window_and_area() {
+        let monitors = [MonitorInfo {
+            bounds: IntRect::from_size(0, 0, 1920, 1080),
+            work_area: IntRect::from_size(0, 0, 1920, 1032),
+            density: None,
+        }];
+        let scene = Scene {
+            window: IntRect::from_size(100, 100, 1600, 800),
+            maximized: false,
+            monitors: &monitors,
+            cursor: Some(Point::new(500.0, 400.0)),
+        };
+        let area = Rect::from_edges(180.0, 100.0, 1620.0, 900.0);
+        let svg = render_svg(&scene, &area);
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        // Everything drawn, plus the margin
+        assert!(svg.contains(r#"viewBox="-40 -40 2000 1160""#));
+        assert!(svg.contains(r##"<rect x="0" y="0" width="1920" height="1080" fill="#e8e8e8""##));
+        assert!(svg.contains(r#"<rect x="0" y="0" width="1920" height="1032" fill="none""#));
+        assert!(svg.contains(">Monitor 1</text>"));
+        assert!(svg.contains(r#"<rect x="100" y="100" width="1600" height="800" fill="none""#));
+        assert!(
+            svg.contains(r##"<rect x="180" y="100" width="1440" height="800" fill="#e04040""##)
+        );
+        assert!(svg.contains(">1440x800</text>"));
+        assert!(svg.contains(r#"<circle cx="500" cy="400""#));
+    }
+}
@@ -0,0 +1,469 @@
+use regex::{Regex, RegexBuilder};
+
+/// The properties of a window that rules are matched against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowInfo {
+    pub title: String,
+    /// Executable file name of the owning process (`krita.exe`).
+    pub process: String,
+    /// Full path of the owning process's executable.
+    pub path: String,
+    /// Window class name.
+    pub class: String,
+}
+
+/// A window property a condition looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    Process,
+    Path,
+    Class,
+    /// Title or process name, for plain patterns without a field.
+    TitleOrProcess,
+}
+
+impl std::str::FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "title" => Ok(Field::Title),
+            "process" => Ok(Field::Process),
+            "path" => Ok(Field::Path),
+            "class" => Ok(Field::Class),
+            _ => Err(format!(
+                "'{s}' is not a window field (expected title, process, path, or class)"
+            )),
+        }
+    }
+}
+
+/// How a condition's pattern is compared with a field. All comparisons
+/// ignore case.
+#[derive(Debug, Clone)]
+enum Matcher {
+    Exact(String),
+    Contains(String),
+    /// `*` matches any run of characters and `?` any single character.
+    Glob(Vec<char>),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn new(mode: &str, pattern: &str) -> Result<Self, String> {
+        match mode.to_ascii_lowercase().as_str() {
+            "exact" => Ok(Matcher::Exact(pattern.to_lowercase())),
+            "contains" => Ok(Matcher::Contains(pattern.to_lowercase())),
+            "glob" => Ok(Matcher::Glob(pattern.to_lowercase().chars().collect())),
+            "regex" => RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| format!("invalid regex '{pattern}': {e}")),
+            _ => Err(format!(
+                "'{mode}' is not a match mode (expected exact, contains, glob, or regex)"
+            )),
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Matcher::Exact(pattern) => value.to_lowercase() == *pattern,
+            Matcher::Contains(pattern) => value.to_lowercase().contains(pattern.as_str()),
+            Matcher::Glob(pattern) => {
+                let value: Vec<char> = value.to_lowercase().chars().collect();
+                glob_matches(pattern, &value)
+            }
+            Matcher::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// Match `value` against a glob `pattern` in full.
+fn glob_matches(pattern: &[char], value: &[char]) -> bool {
+    // Greedy scan that backtracks to the most recent `*` on a mismatch
+    let (mut p, mut v) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match star {
+                // Let the `*` swallow one more character and retry
+                Some((star_p, star_v)) => {
+                    star = Some((star_p, star_v + 1));
+                    p = star_p + 1;
+                    v = star_v + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A single `field[:mode]=pattern` test.
+#[derive(Debug, Clone)]
+struct Condition {
+    field: Field,
+    matcher: Matcher,
+}
+
+impl Condition {
+    fn matches(&self, window: &WindowInfo) -> bool {
+        let test = |value: &str| !value.is_empty() && self.matcher.matches(value);
+        match self.field {
+            Field::Title => test(&window.title),
+            Field::Process => test(&window.process),
+            Field::Path => test(&window.path),
+            Field::Class => test(&window.class),
+            Field::TitleOrProcess => test(&window.title) || test(&window.process),
+        }
+    }
+}
+
+impl std::str::FromStr for Condition {
+    type Err = String;
+
+    /// Parses `field[:mode]=pattern`, or a plain pattern matched as a
+    /// substring of the title or process name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some((key, pattern)) = s.split_once('=') else {
+            if s.is_empty() {
+                return Err("empty pattern in rule".to_string());
+            }
+            return Ok(Condition {
+                field: Field::TitleOrProcess,
+                matcher: Matcher::Contains(s.to_lowercase()),
+            });
+        };
+        let (field, mode) = key.split_once(':').unwrap_or((key, "contains"));
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(format!("empty pattern in '{s}'"));
+        }
+        Ok(Condition {
+            field: field.trim().parse()?,
+            matcher: Matcher::new(mode.trim(), pattern)?,
+        })
+    }
+}
+
+/// A window match rule: conditions joined with `&&`, and alternatives of
+/// those joined with `||` (`&&` binds tighter).
+///
+/// Each condition is `field[:mode]=pattern`, with field one of `title`,
+/// `process`, `path` or `class` and mode one of `contains` (default),
+/// `exact`, `glob` or `regex`. A plain pattern without `=` matches the title
+/// or process name by substring, e.g. `krita`.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    source: String,
+    /// Alternatives, each matching if all its conditions do.
+    any_of: Vec<Vec<Condition>>,
+}
+
+impl Rule {
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        self.any_of
+            .iter()
+            .any(|all_of| all_of.iter().all(|c| c.matches(window)))
+    }
+}
+
+impl std::str::FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let any_of = s
+            .split("||")
+            .map(|alternative| alternative.split("&&").map(str::parse).collect())
+            .collect::<Result<_, String>>()?;
+        Ok(Rule {
+            source: s.trim().to_string(),
+            any_of,
+        })
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// The windows to track: those matching one of the include rules and none
+/// of the exclude rules. Exclusions always win.
+///
+/// Include rules are in priority order, highest first.
+#[derive(Debug, Clone)]
+pub struct TargetRules {
+    pub include: Vec<Rule>,
+    pub exclude: Vec<Rule>,
+}
+
+impl TargetRules {
+    /// Index of the highest-priority include rule the window matches, or
+    /// `None` if it matches none or is excluded.
+    pub fn find(&self, window: &WindowInfo) -> Option<usize> {
+        if self.excludes(window) {
+            return None;
+        }
+        self.include.iter().position(|rule| rule.matches(window))
+    }
+
+    pub fn excludes(&self, window: &WindowInfo) -> bool {
+        self.exclude.iter().any(|rule| rule.matches(window))
+    }
+
+    /// Pick the window matching the highest-priority include rule, with the
+    /// index of that rule. `windows` are in Z-order, so the topmost wins
+    /// among windows matching the same rule.
+    pub fn best_match<T>(
+        &self,
+        windows: impl IntoIterator<Item = (T, WindowInfo)>,
+    ) -> Option<(T, usize)> {
+        let mut best: Option<(T, usize)> = None;
+        for (window, info) in windows {
+            let Some(index) = self.find(&info) else {
+                continue;
+            };
+            if best
+                .as_ref()
+                .is_none_or(|&(_, best_index)| index < best_index)
+            {
+                best = Some((window, index));
+                if index == 0 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn krita() -> WindowInfo {
+        WindowInfo {
+            title: "Untitled — Krita".to_string(),
+            process: "krita.exe".to_string(),
+            path: r"C:\Program Files\Krita (x64)\bin\krita.exe".to_string(),
+            class: "Qt5152QWindowIcon".to_string(),
+        }
+    }
+
+    fn browser() -> WindowInfo {
+        WindowInfo {
+            title: "Krita tutorial - YouTube".to_string(),
+            process: "chrome.exe".to_string(),
+            path: r"C:\Program Files\Google\Chrome\Application\chrome.exe".to_string(),
+            class: "Chrome_WidgetWin_1".to_string(),
+        }
+    }
+
+    fn rule(s: &str) -> Rule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn plain_pattern_matches_title_or_process() {
+        assert!(rule("KRITA").matches(&krita()));
+        assert!(rule("krita").matches(&browser()));
+        assert!(rule("chrome.exe").matches(&browser()));
+        assert!(!rule("photoshop").matches(&krita()));
+    }
+
+    #[test]
+    fn field_rules_only_look_at_their_field() {
+        assert!(rule("process=krita").matches(&krita()));
+        assert!(!rule("process=krita").matches(&browser()));
+        assert!(rule("title=youtube").matches(&browser()));
+        assert!(rule(r"path=\krita (x64)\").matches(&krita()));
+        assert!(rule("class=qwindowicon").matches(&krita()));
+        assert!(!rule("class=qwindowicon").matches(&browser()));
+    }
+
+    #[test]
+    fn exact_match_ignores_case_only() {
+        assert!(rule("process:exact=Krita.EXE").matches(&krita()));
+        assert!(!rule("process:exact=krita").matches(&krita()));
+    }
+
+    #[test]
+    fn glob_match() {
+        assert!(rule("class:glob=Qt*QWindowIcon").matches(&krita()));
+        assert!(rule("process:glob=kr?ta.*").matches(&krita()));
+        assert!(rule("title:glob=*krita").matches(&krita()));
+        assert!(!rule("title:glob=krita*").matches(&krita()));
+        assert!(rule("path:glob=c:\\program files\\*\\bin\\*.exe").matches(&krita()));
+        assert!(!rule("class:glob=Qt?QWindowIcon").matches(&krita()));
+    }
+
+    #[test]
+    fn glob_backtracks_over_stars() {
+        let pattern: Vec<char> = "a*b*c".chars().collect();
+        for (value, expected) in [
+            ("abc", true),
+            ("aXbYbZc", true),
+            ("abcb", false),
+            ("ab", false),
+            ("a**c", false),
+        ] {
+            let value: Vec<char> = value.chars().collect();
+            assert_eq!(glob_matches(&pattern, &value), expected, "{value:?}");
+        }
+        assert!(glob_matches(&['*'], &[]));
+        assert!(!glob_matches(&['?'], &[]));
+    }
+
+    #[test]
+    fn regex_match() {
+        assert!(rule(r"title:regex=^untitled\b").matches(&krita()));
+        assert!(rule(r"class:regex=^qt\d+qwindow").matches(&krita()));
+        assert!(!rule(r"title:regex=^krita").matches(&krita()));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let r = rule("process=chrome && title=krita || process:exact=krita.exe");
+        assert!(r.matches(&krita()));
+        assert!(r.matches(&browser()));
+
+        let r = rule("process=chrome && title=photoshop || process=mspaint");
+        assert!(!r.matches(&browser()));
+
+        // Two Electron-style apps told apart by exe path
+        let r = rule(r"class=Chrome_WidgetWin && path=\Google\");
+        assert!(r.matches(&browser()));
+        assert!(!r.matches(&WindowInfo {
+            path: r"C:\Users\me\AppData\Local\Figma\Figma.exe".to_string(),
+            ..browser()
+        }));
+    }
+
+    #[test]
+    fn empty_fields_never_match() {
+        let untitled = WindowInfo {
+            title: String::new(),
+            ..krita()
+        };
+        assert!(!rule("title:glob=*").matches(&untitled));
+        assert!(rule("krita").matches(&untitled));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        for s in [
+            "",
+            "krita &&",
+            "name=krita",
+            "title:fuzzy=krita",
+            "title=",
+            "title:regex=(unclosed",
+        ] {
+            assert!(s.parse::<Rule>().is_err(), "{s:?}");
+        }
+    }
+
+    fn target(include: &str, exclude: &[&str]) -> TargetRules {
+        TargetRules {
+            include: vec![rule(include)],
+            exclude: exclude.iter().map(|s| rule(s)).collect(),
+        }
+    }
+
+    #[test]
+    fn exclude_vetoes_include() {
+        let rules = target("krita", &["process=chrome"]);
+        assert_eq!(rules.find(&krita()), Some(0));
+        assert_eq!(rules.find(&browser()), None);
+        assert!(rules.excludes(&browser()));
+    }
+
+    #[test]
+    fn exclude_wins_even_when_include_is_specific() {
+        // The include rule names this exact window, but an exclusion still
+        // applies
+        let rules = target("process:exact=krita.exe", &["title=untitled"]);
+        assert_eq!(rules.find(&krita()), None);
+    }
+
+    #[test]
+    fn exclude_alone_never_selects_a_window() {
+        // Matching no exclusion isn't enough; the include rule must match
+        let rules = target("photoshop", &["process=chrome"]);
+        assert_eq!(rules.find(&krita()), None);
+        assert!(!rules.excludes(&krita()));
+    }
+
+    #[test]
+    fn any_of_several_excludes_vetoes() {
+        let rules = target("krita", &["title=youtube", "class:glob=Qt*"]);
+        assert_eq!(rules.find(&krita()), None);
+        assert_eq!(rules.find(&browser()), None);
+
+        let rules = target("krita", &["title=youtube && process=firefox"]);
+        assert_eq!(rules.find(&browser()), Some(0));
+    }
+
+    fn viewer() -> WindowInfo {
+        WindowInfo {
+            title: "ref.png - PureRef".to_string(),
+            process: "PureRef.exe".to_string(),
+            path: r"C:\Program Files\PureRef\PureRef.exe".to_string(),
+            class: "Qt5QWindowIcon".to_string(),
+        }
+    }
+
+    #[test]
+    fn find_returns_highest_priority_rule() {
+        let rules = TargetRules {
+            include: vec![rule("process=krita"), rule("class:glob=Qt*")],
+            exclude: Vec::new(),
+        };
+        assert_eq!(rules.find(&krita()), Some(0));
+        assert_eq!(rules.find(&viewer()), Some(1));
+        assert_eq!(rules.find(&browser()), None);
+    }
+
+    #[test]
+    fn best_match_prefers_priority_over_z_order() {
+        let rules = TargetRules {
+            include: vec![rule("process=krita"), rule("process=pureref")],
+            exclude: vec![rule("process=chrome")],
+        };
+        // Topmost first: the viewer is above Krita, the browser above both
+        let windows = [(1, browser()), (2, viewer()), (3, krita())];
+        assert_eq!(rules.best_match(windows.clone()), Some((3, 0)));
+
+        // Without Krita open, the viewer is picked
+        assert_eq!(rules.best_match(windows[..2].to_vec()), Some((2, 1)));
+        assert_eq!(rules.best_match(windows[..1].to_vec()), None);
+    }
+
+    #[test]
+    fn best_match_breaks_ties_by_z_order() {
+        let rules = TargetRules {
+            include: vec![rule("krita")],
+            exclude: Vec::new(),
+        };
+        let windows = [(1, viewer()), (2, krita()), (3, krita())];
+        assert_eq!(rules.best_match(windows), Some((2, 0)));
+    }
+
+    #[test]
+    fn display_shows_source() {
+        assert_eq!(rule(" process=krita ").to_string(), "process=krita");
+    }
+}
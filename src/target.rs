@@ -0,0 +1,181 @@
+use crate::canvas::CanvasSelector;
+use crate::geometry::{FitMode, Insets};
+use crate::pipeline::Settings;
+use crate::rules::Rule;
+
+/// A `--target` value: a match rule, optionally followed by settings for
+/// the windows it matches, each as `;key=value`
+/// (`process=krita;rotation=90;fit=contain;insets=40;canvas=largest`).
+/// A `;` that is part of the rule is written `\;`.
+///
+/// Settings left out fall back to the command-line options.
+#[derive(Debug, Clone)]
+pub struct TargetSpec {
+    pub rule: Rule,
+    pub rotation: Option<f64>,
+    pub fit: Option<FitMode>,
+    pub insets: Option<Insets>,
+    pub canvas: Option<CanvasSelector>,
+}
+
+impl TargetSpec {
+    /// The mapping settings for this target, based on the global `base`.
+    pub fn settings(&self, base: &Settings) -> Settings {
+        Settings {
+            fit_mode: self.fit.unwrap_or(base.fit_mode),
+            insets: self.insets.unwrap_or(base.insets),
+            ..base.clone()
+        }
+    }
+}
+
+impl std::str::FromStr for TargetSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = split_settings(s).into_iter();
+        let mut spec = TargetSpec {
+            rule: parts.next().unwrap_or_default().parse()?,
+            rotation: None,
+            fit: None,
+            insets: None,
+            canvas: None,
+        };
+        for setting in parts {
+            let (key, value) = setting.split_once('=').ok_or_else(|| {
+                format!(
+                    "target setting '{setting}' must be key=value (write \\; for a ';' in the rule)"
+                )
+            })?;
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "rotation" => spec.rotation = Some(parse_rotation(value)?),
+                "fit" => spec.fit = Some(value.parse()?),
+                "insets" => spec.insets = Some(value.parse()?),
+                "canvas" => spec.canvas = Some(value.parse()?),
+                key => {
+                    return Err(format!(
+                        "'{key}' is not a target setting (expected rotation, fit, insets, or canvas)"
+                    ));
+                }
+            }
+        }
+        Ok(spec)
+    }
+}
+
+/// Split a `--target` value at each `;`, except where it is escaped as `\;`.
+fn split_settings(s: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&';') => {
+                chars.next();
+                parts.last_mut().unwrap().push(';');
+            }
+            ';' => parts.push(String::new()),
+            c => parts.last_mut().unwrap().push(c),
+        }
+    }
+    parts
+}
+
+/// Parse a rotation in degrees, normalized to `[0, 360)`.
+pub fn parse_rotation(s: &str) -> Result<f64, String> {
+    let v: f64 = s
+        .parse()
+        .map_err(|_| format!("'{s}' is not a valid number"))?;
+    if !v.is_finite() {
+        return Err("rotation must be a finite number of degrees".to_string());
+    }
+    Ok(v.rem_euclid(360.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Anchor, Clamp, Inset};
+    use crate::rules::WindowInfo;
+
+    fn base() -> Settings {
+        Settings {
+            max_distortion: 0.1,
+            anchor: Anchor::Top,
+            clamp: Clamp::Monitor,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn plain_rule_keeps_global_settings() {
+        let spec: TargetSpec = "krita".parse().unwrap();
+        assert_eq!(spec.rotation, None);
+        assert!(spec.canvas.is_none());
+        let settings = spec.settings(&base());
+        assert_eq!(settings.fit_mode, FitMode::Cover);
+        assert_eq!(settings.insets, Insets::default());
+        assert!(spec.rule.matches(&WindowInfo {
+            process: "krita.exe".to_string(),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn settings_override_only_their_own_option() {
+        let spec: TargetSpec =
+            "process:exact=photoshop.exe && title=psd; rotation=-90; fit=crop; insets=40,0,0,0"
+                .parse()
+                .unwrap();
+        assert_eq!(spec.rotation, Some(270.0));
+        let settings = spec.settings(&base());
+        assert_eq!(settings.fit_mode, FitMode::Crop);
+        assert_eq!(settings.insets.left, Inset::Pixels(40.0));
+        assert_eq!(settings.insets.top, Inset::Pixels(0.0));
+        // Everything else comes from the command line
+        assert_eq!(settings.anchor, Anchor::Top);
+        assert_eq!(settings.clamp, Clamp::Monitor);
+        assert_eq!(settings.max_distortion, 0.1);
+    }
+
+    #[test]
+    fn canvas_value_may_hold_a_rule() {
+        let spec: TargetSpec = "krita;canvas=class:glob=Qt*GLWidget || class=Canvas;rotation=90"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            spec.canvas.unwrap().to_string(),
+            "class:glob=Qt*GLWidget || class=Canvas"
+        );
+        assert_eq!(spec.rotation, Some(90.0));
+    }
+
+    #[test]
+    fn escaped_semicolon_is_part_of_the_rule() {
+        let spec: TargetSpec = r"title=Draft\; final;rotation=90".parse().unwrap();
+        assert_eq!(spec.rotation, Some(90.0));
+        assert!(spec.rule.matches(&WindowInfo {
+            title: "Draft; final.kra".to_string(),
+            ..Default::default()
+        }));
+
+        // An unescaped one starts a setting, and the error says how to escape
+        let err = "title=Draft; final".parse::<TargetSpec>().unwrap_err();
+        assert!(err.contains(r"\;"), "{err}");
+    }
+
+    #[test]
+    fn invalid_specs_are_rejected() {
+        for s in [
+            "",
+            "krita;rotation",
+            "krita;rotation=left",
+            "krita;fit=squash",
+            "krita;scale=2",
+            "title:fuzzy=krita;fit=cover",
+            "krita;canvas=QOpenGLWidget",
+        ] {
+            assert!(s.parse::<TargetSpec>().is_err(), "{s:?}");
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use crate::canvas::{CanvasSelector, Child};
+use crate::eligibility::{DIALOG_CLASS, Decision, Eligibility, WindowTraits};
+use crate::geometry::IntRect;
+use crate::rules::{TargetRules, WindowInfo};
 use windows::Win32::Foundation::*;
 use windows::Win32::Graphics::Dwm::*;
 use windows::Win32::System::Threading::*;
@@ -17,8 +21,16 @@ pub fn get_window_title(hwnd: HWND) -> String {
     }
 }
 
-/// Get the process executable name for a window's owning process.
-pub fn get_process_name(hwnd: HWND) -> String {
+/// Get the class name of a window.
+pub fn get_window_class(hwnd: HWND) -> String {
+    // Class names are at most 256 characters
+    let mut buf = [0u16; 257];
+    let len = unsafe { GetClassNameW(hwnd, &mut buf) };
+    String::from_utf16_lossy(&buf[..len.max(0) as usize])
+}
+
+/// Get the full executable path of a window's owning process.
+pub fn get_process_path(hwnd: HWND) -> String {
     unsafe {
         let mut pid = 0u32;
         GetWindowThreadProcessId(hwnd, Some(&mut pid));
@@ -41,34 +53,79 @@ pub fn get_process_name(hwnd: HWND) -> String {
         let _ = CloseHandle(process);
 
         if result.is_ok() {
-            let full_path = String::from_utf16_lossy(&buf[..size as usize]);
-            full_path
-                .rsplit('\\')
-                .next()
-                .unwrap_or(&full_path)
-                .to_string()
+            String::from_utf16_lossy(&buf[..size as usize])
         } else {
             String::new()
         }
     }
 }
 
-/// Check if a window matches the target (case-insensitive substring match
-/// against window title or process name).
-pub fn matches_target(hwnd: HWND, target: &str) -> bool {
-    let target_lower = target.to_lowercase();
+/// Collect the properties of a window that target rules match against.
+pub fn window_info(hwnd: HWND) -> WindowInfo {
+    let path = get_process_path(hwnd);
+    WindowInfo {
+        title: get_window_title(hwnd),
+        process: path.rsplit('\\').next().unwrap_or(&path).to_string(),
+        class: get_window_class(hwnd),
+        path,
+    }
+}
+
+/// Find the highest-priority target a window matches. Returns `None` if it
+/// matches none or is excluded.
+pub fn match_target(hwnd: HWND, target: &TargetRules) -> Option<usize> {
+    target.find(&window_info(hwnd))
+}
+
+/// Check if a window matches one of the target's exclude rules.
+pub fn is_excluded(hwnd: HWND, target: &TargetRules) -> bool {
+    target.excludes(&window_info(hwnd))
+}
 
-    let title = get_window_title(hwnd);
-    if !title.is_empty() && title.to_lowercase().contains(&target_lower) {
-        return true;
+/// Get the attributes that decide whether a window can be tracked.
+pub fn window_traits(hwnd: HWND) -> WindowTraits {
+    let ex_style = unsafe { GetWindowLongW(hwnd, GWL_EXSTYLE) } as u32;
+    let rect = get_window_rect(hwnd);
+    WindowTraits {
+        owned: unsafe { GetWindow(hwnd, GW_OWNER) }.is_ok(),
+        tool_window: ex_style & WS_EX_TOOLWINDOW.0 != 0,
+        dialog: get_window_class(hwnd) == DIALOG_CLASS,
+        width: rect.map_or(0, |r| r.width()),
+        height: rect.map_or(0, |r| r.height()),
     }
+}
 
-    let process = get_process_name(hwnd);
-    if !process.is_empty() && process.to_lowercase().contains(&target_lower) {
-        return true;
+/// Find the window to track for `hwnd` and the index of the target it
+/// matches: the window itself, or the top-level window owning it if it is a
+/// popup of a trackable window. Returns `None` if neither matches a target
+/// or the one that does can't be tracked.
+///
+/// Targets are matched first, so the eligibility policy only runs (and only
+/// logs) for windows of the tracked apps.
+pub fn trackable_match(
+    hwnd: HWND,
+    target: &TargetRules,
+    policy: &Eligibility,
+) -> Option<(HWND, usize)> {
+    let owner = unsafe { GetAncestor(hwnd, GA_ROOTOWNER) };
+    let owner = (!owner.is_invalid() && owner != hwnd).then_some(owner);
+    let window_match = match_target(hwnd, target);
+    let owner_match = owner.and_then(|owner| Some((owner, match_target(owner, target)?)));
+    if window_match.is_none() && owner_match.is_none() {
+        return None;
     }
 
-    false
+    let owner_traits = owner_match.map(|(owner, _)| window_traits(owner));
+    match policy.decide(&window_traits(hwnd), owner_traits.as_ref()) {
+        Decision::Window => window_match.map(|index| (hwnd, index)),
+        Decision::Owner => owner_match,
+        Decision::Neither(reason) => {
+            if window_match.is_some() {
+                log::debug!("Ignoring \"{}\": {reason}", get_window_title(hwnd));
+            }
+            None
+        }
+    }
 }
 
 /// Check if a window is visible and not minimized.
@@ -81,12 +138,18 @@ pub fn is_minimized(hwnd: HWND) -> bool {
     unsafe { IsIconic(hwnd).as_bool() }
 }
 
-/// Get the visible window rectangle as (left, top, width, height).
+/// Check if a window is maximized (zoomed).
+pub fn is_maximized(hwnd: HWND) -> bool {
+    unsafe { IsZoomed(hwnd).as_bool() }
+}
+
+/// Get the visible window rectangle.
 /// Uses DwmGetWindowAttribute(DWMWA_EXTENDED_FRAME_BOUNDS) to get the actual
 /// visible bounds, excluding invisible DPI-scaled borders on Windows 10/11.
-/// Falls back to GetWindowRect if DWM is unavailable.
+/// Falls back to GetWindowRect if DWM is unavailable, and for child windows
+/// (canvases), which have no DWM frame.
 /// Returns `None` if the rect has zero or negative dimensions.
-pub fn get_window_rect(hwnd: HWND) -> Option<(i32, i32, i32, i32)> {
+pub fn get_window_rect(hwnd: HWND) -> Option<IntRect> {
     unsafe {
         let mut rect = RECT::default();
 
@@ -100,24 +163,82 @@ pub fn get_window_rect(hwnd: HWND) -> Option<(i32, i32, i32, i32)> {
         .is_ok()
             || GetWindowRect(hwnd, &mut rect).is_ok();
 
-        if got_rect {
-            let width = rect.right - rect.left;
-            let height = rect.bottom - rect.top;
-            if width > 0 && height > 0 {
-                return Some((rect.left, rect.top, width, height));
-            }
+        if !got_rect {
+            return None;
         }
-        None
+        let rect = IntRect::from(rect);
+        (!rect.is_empty()).then_some(rect)
     }
 }
 
-/// Find the first matching window. Prefers the foreground window if it matches.
-pub fn find_matching_window(target: &str) -> Option<HWND> {
+impl From<RECT> for IntRect {
+    fn from(rect: RECT) -> Self {
+        IntRect {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        }
+    }
+}
+
+/// Find the canvas of a top-level window: the visible descendant picked by
+/// `selector`, or `None` if it has none.
+pub fn find_canvas(hwnd: HWND, selector: &CanvasSelector) -> Option<HWND> {
+    // EnumChildWindows lists all descendants, not just direct children
+    let mut descendants: Vec<HWND> = Vec::new();
+    unsafe {
+        let _ = EnumChildWindows(
+            Some(hwnd),
+            Some(collect_windows_callback),
+            LPARAM(&raw mut descendants as isize),
+        );
+    }
+
+    // Children share their window's process, so it is looked up once
+    let parent = window_info(hwnd);
+    let (handles, children): (Vec<HWND>, Vec<Child>) = descendants
+        .into_iter()
+        .filter(|&child| unsafe { IsWindowVisible(child) }.as_bool())
+        .filter_map(|child| {
+            let info = WindowInfo {
+                title: get_window_title(child),
+                class: get_window_class(child),
+                ..parent.clone()
+            };
+            let id = unsafe { GetDlgCtrlID(child) };
+            Some((
+                child,
+                Child {
+                    info,
+                    id,
+                    rect: get_window_rect(child)?,
+                },
+            ))
+        })
+        .unzip();
+    selector.select(&children).map(|i| handles[i])
+}
+
+/// Check if `hwnd` is a descendant of `ancestor`.
+pub fn is_descendant(ancestor: HWND, hwnd: HWND) -> bool {
+    unsafe { IsChild(ancestor, hwnd).as_bool() }
+}
+
+/// Find the window to track and the index of the target it matches.
+/// Prefers the foreground window (or the window owning it) if it matches any
+/// target; otherwise the eligible window matching the highest-priority
+/// target, topmost first.
+pub fn find_matching_window(target: &TargetRules, policy: &Eligibility) -> Option<(HWND, usize)> {
     let fg = unsafe { GetForegroundWindow() };
-    if !fg.0.is_null() && is_valid_window(fg) && matches_target(fg, target) {
-        return Some(fg);
+    if !fg.0.is_null()
+        && let Some((fg, index)) = trackable_match(fg, target, policy)
+        && is_valid_window(fg)
+    {
+        return Some((fg, index));
     }
 
+    // EnumWindows lists top-level windows in Z-order
     let mut windows: Vec<HWND> = Vec::new();
     unsafe {
         let _ = EnumWindows(
@@ -126,9 +247,12 @@ pub fn find_matching_window(target: &str) -> Option<HWND> {
         );
     }
 
-    windows
-        .into_iter()
-        .find(|&hwnd| is_valid_window(hwnd) && matches_target(hwnd, target))
+    target.best_match(
+        windows
+            .into_iter()
+            .filter(|&hwnd| is_valid_window(hwnd) && policy.check(&window_traits(hwnd)).is_ok())
+            .map(|hwnd| (hwnd, window_info(hwnd))),
+    )
 }
 
 unsafe extern "system" fn collect_windows_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {